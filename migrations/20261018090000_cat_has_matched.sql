@@ -0,0 +1,9 @@
+-- Persist whether a cat has an approved match so approval can mark both cats
+ALTER TABLE cats ADD COLUMN IF NOT EXISTS has_matched BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE cats SET has_matched = TRUE
+WHERE id IN (
+    SELECT user_cat_id FROM cat_matches WHERE status = 'approved'
+    UNION
+    SELECT match_cat_id FROM cat_matches WHERE status = 'approved'
+);
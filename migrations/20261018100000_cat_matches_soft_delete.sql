@@ -0,0 +1,17 @@
+-- Keep match history when a cat is deleted: the request is soft-deleted and the
+-- reference to the removed cat is nulled instead of cascading the delete.
+ALTER TABLE cat_matches ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP;
+ALTER TABLE cat_matches ADD COLUMN IF NOT EXISTS issuer_id INT REFERENCES users(id) ON DELETE NO ACTION ON UPDATE NO ACTION;
+
+UPDATE cat_matches m SET issuer_id = c.user_id FROM cats c WHERE c.id = m.user_cat_id AND m.issuer_id IS NULL;
+
+ALTER TABLE cat_matches ALTER COLUMN issuer_id SET NOT NULL;
+ALTER TABLE cat_matches ALTER COLUMN user_cat_id DROP NOT NULL;
+ALTER TABLE cat_matches ALTER COLUMN match_cat_id DROP NOT NULL;
+
+ALTER TABLE cat_matches DROP CONSTRAINT IF EXISTS cat_matches_user_cat_id_fkey;
+ALTER TABLE cat_matches DROP CONSTRAINT IF EXISTS cat_matches_match_cat_id_fkey;
+ALTER TABLE cat_matches ADD CONSTRAINT cat_matches_user_cat_id_fkey
+    FOREIGN KEY (user_cat_id) REFERENCES cats(id) ON DELETE SET NULL ON UPDATE CASCADE;
+ALTER TABLE cat_matches ADD CONSTRAINT cat_matches_match_cat_id_fkey
+    FOREIGN KEY (match_cat_id) REFERENCES cats(id) ON DELETE SET NULL ON UPDATE CASCADE;
@@ -0,0 +1,5 @@
+-- Trigram indexes backing case-insensitive partial and fuzzy cat search
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX IF NOT EXISTS idx_cats_name_trgm ON cats USING GIN (name gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS idx_cats_description_trgm ON cats USING GIN (description gin_trgm_ops);
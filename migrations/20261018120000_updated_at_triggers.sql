@@ -0,0 +1,19 @@
+-- Keep updated_at current on every row update
+CREATE OR REPLACE FUNCTION set_updated_at() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated_at = CURRENT_TIMESTAMP;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS users_set_updated_at ON users;
+CREATE TRIGGER users_set_updated_at BEFORE UPDATE ON users
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+DROP TRIGGER IF EXISTS cats_set_updated_at ON cats;
+CREATE TRIGGER cats_set_updated_at BEFORE UPDATE ON cats
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+DROP TRIGGER IF EXISTS cat_matches_set_updated_at ON cat_matches;
+CREATE TRIGGER cat_matches_set_updated_at BEFORE UPDATE ON cat_matches
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
@@ -0,0 +1,13 @@
+-- Row version for optimistic concurrency, exposed to clients as an ETag
+ALTER TABLE cats ADD COLUMN IF NOT EXISTS version INT NOT NULL DEFAULT 1;
+
+CREATE OR REPLACE FUNCTION bump_version() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.version = OLD.version + 1;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS cats_bump_version ON cats;
+CREATE TRIGGER cats_bump_version BEFORE UPDATE ON cats
+    FOR EACH ROW EXECUTE FUNCTION bump_version();
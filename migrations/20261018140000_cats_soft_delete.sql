@@ -0,0 +1,4 @@
+-- Soft delete for cats; rows are purged once the restore window has passed
+ALTER TABLE cats ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP;
+
+CREATE INDEX IF NOT EXISTS idx_cats_deleted_at ON cats (deleted_at) WHERE deleted_at IS NOT NULL;
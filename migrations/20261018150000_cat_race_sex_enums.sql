@@ -0,0 +1,10 @@
+-- Replace the race and sex CHECK constraints with enum types shared with the
+-- Rust `Race` and `Sex` enums
+CREATE TYPE cat_race AS ENUM ('Persian', 'Maine Coon', 'Siamese', 'Ragdoll', 'Bengal', 'Sphynx', 'British Shorthair', 'Abyssinian', 'Scottish Fold', 'Birman');
+CREATE TYPE cat_sex AS ENUM ('male', 'female');
+
+ALTER TABLE cats DROP CONSTRAINT IF EXISTS cats_race_check;
+ALTER TABLE cats DROP CONSTRAINT IF EXISTS cats_sex_check;
+
+ALTER TABLE cats ALTER COLUMN race TYPE cat_race USING race::cat_race;
+ALTER TABLE cats ALTER COLUMN sex TYPE cat_sex USING sex::cat_sex;
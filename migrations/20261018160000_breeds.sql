@@ -0,0 +1,35 @@
+-- Move the race list into an admin-managed breeds table. Cats keep storing the
+-- display name, which follows renames through the foreign key.
+CREATE TABLE IF NOT EXISTS breeds (
+    id SERIAL PRIMARY KEY,
+    slug VARCHAR(50) NOT NULL UNIQUE,
+    name VARCHAR(30) NOT NULL UNIQUE,
+    description VARCHAR(200) NOT NULL DEFAULT '',
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO breeds (slug, name) VALUES
+    ('persian', 'Persian'),
+    ('maine-coon', 'Maine Coon'),
+    ('siamese', 'Siamese'),
+    ('ragdoll', 'Ragdoll'),
+    ('bengal', 'Bengal'),
+    ('sphynx', 'Sphynx'),
+    ('british-shorthair', 'British Shorthair'),
+    ('abyssinian', 'Abyssinian'),
+    ('scottish-fold', 'Scottish Fold'),
+    ('birman', 'Birman')
+ON CONFLICT DO NOTHING;
+
+DROP TRIGGER IF EXISTS breeds_set_updated_at ON breeds;
+CREATE TRIGGER breeds_set_updated_at BEFORE UPDATE ON breeds
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+ALTER TABLE cats ALTER COLUMN race TYPE VARCHAR(30) USING race::text;
+DROP TYPE IF EXISTS cat_race;
+
+ALTER TABLE cats ADD CONSTRAINT cats_race_fkey
+    FOREIGN KEY (race) REFERENCES breeds(name) ON DELETE RESTRICT ON UPDATE CASCADE;
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS is_admin BOOLEAN NOT NULL DEFAULT FALSE;
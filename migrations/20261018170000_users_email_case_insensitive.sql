@@ -0,0 +1,29 @@
+-- Emails are unique regardless of case; login looks them up the same way.
+--
+-- The old constraint allowed addresses that differ only in case. Those rows
+-- can't be merged automatically, so stop here and list them; dedupe them by
+-- hand (e.g. rename or delete the unused account) and rerun the migration.
+DO $$
+DECLARE
+    duplicates TEXT;
+BEGIN
+    SELECT string_agg(format('%s (ids %s)', lower_email, ids), '; ')
+    INTO duplicates
+    FROM (
+        SELECT LOWER(email) AS lower_email, string_agg(id::TEXT, ', ' ORDER BY id) AS ids
+        FROM users
+        GROUP BY LOWER(email)
+        HAVING COUNT(*) > 1
+    ) AS clashes;
+
+    IF duplicates IS NOT NULL THEN
+        RAISE EXCEPTION 'users.email has case-insensitive duplicates: %', duplicates
+            USING HINT = 'Dedupe these accounts manually before rerunning the migration.';
+    END IF;
+END $$;
+
+CREATE UNIQUE INDEX IF NOT EXISTS users_email_lower_key ON users (LOWER(email));
+
+-- Superseded by users_email_lower_key.
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_email_key;
+DROP INDEX IF EXISTS idx_users_email;
@@ -0,0 +1,15 @@
+-- Only one pending request per pair of cats. Older duplicates carry the same
+-- request, so keep the first one and reject the rest before indexing.
+UPDATE cat_matches m SET status = 'rejected'
+WHERE m.status = 'pending' AND m.deleted_at IS NULL AND EXISTS (
+    SELECT 1 FROM cat_matches earlier
+    WHERE earlier.user_cat_id = m.user_cat_id
+        AND earlier.match_cat_id = m.match_cat_id
+        AND earlier.status = 'pending'
+        AND earlier.deleted_at IS NULL
+        AND earlier.id < m.id
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS cat_matches_pending_pair_key
+    ON cat_matches (user_cat_id, match_cat_id)
+    WHERE status = 'pending' AND deleted_at IS NULL;
@@ -0,0 +1,171 @@
+use crate::{
+    entities::{
+        breed::{Breed, CreateBreedRequest, UpdateBreedRequest},
+        ResponseWrapper,
+    },
+    errors::{sqlstate, AppError, FOREIGN_KEY_VIOLATION, UNIQUE_VIOLATION},
+    helpers::breeds::invalidate_breed_cache,
+    middlewares::{auth::Admin, payload::Json},
+    repositories::breed::{delete_breed, find_many_breeds, insert_breed, update_breed},
+    AppState,
+};
+use actix_web::{delete, get, post, put, web, web::Data, HttpResponse, Responder};
+use validator::Validate;
+
+#[get("")]
+async fn get_breeds(state: Data<AppState>) -> Result<impl Responder, AppError> {
+    let breeds = find_many_breeds(&state.db).await?;
+
+    Ok(HttpResponse::Ok().json(ResponseWrapper::<Vec<Breed>> {
+        message: "Breeds fetched successfully".to_string(),
+        data: Some(breeds),
+        meta: None,
+    }))
+}
+
+#[post("")]
+async fn create_breed(
+    state: Data<AppState>,
+    Admin(admin): Admin,
+    payload: Json<CreateBreedRequest>,
+) -> Result<impl Responder, AppError> {
+    payload.validate()?;
+
+    let breed = insert_breed(&state.db, payload.into_inner())
+        .await
+        .map_err(breed_write_error)?;
+    invalidate_breed_cache();
+    log::info!("Breed {} created by user {}", breed.slug, admin.id);
+
+    Ok(HttpResponse::Created().json(ResponseWrapper::<Breed> {
+        message: "Breed created successfully".to_string(),
+        data: Some(breed),
+        meta: None,
+    }))
+}
+
+#[put("/{slug}")]
+async fn modify_breed(
+    state: Data<AppState>,
+    Admin(admin): Admin,
+    slug: web::Path<String>,
+    payload: Json<UpdateBreedRequest>,
+) -> Result<impl Responder, AppError> {
+    payload.validate()?;
+
+    let breed = update_breed(&state.db, &slug, payload.into_inner())
+        .await
+        .map_err(breed_write_error)?;
+    invalidate_breed_cache();
+    log::info!("Breed {} updated by user {}", breed.slug, admin.id);
+
+    Ok(HttpResponse::Ok().json(ResponseWrapper::<Breed> {
+        message: "Breed updated successfully".to_string(),
+        data: Some(breed),
+        meta: None,
+    }))
+}
+
+#[delete("/{slug}")]
+async fn remove_breed(
+    state: Data<AppState>,
+    Admin(admin): Admin,
+    slug: web::Path<String>,
+) -> Result<impl Responder, AppError> {
+    delete_breed(&state.db, &slug)
+        .await
+        .map_err(breed_write_error)?;
+    invalidate_breed_cache();
+    log::info!("Breed {} deleted by user {}", slug, admin.id);
+
+    Ok(HttpResponse::Ok().json(ResponseWrapper::<()> {
+        message: "Breed deleted successfully".to_string(),
+        data: None,
+        meta: None,
+    }))
+}
+
+/// Maps a failed breed write: a missing slug is a 404, and a duplicate slug or
+/// name, or a breed still used by cats, is a 409.
+fn breed_write_error(err: sqlx::Error) -> AppError {
+    match (&err, sqlstate(&err).as_deref()) {
+        (sqlx::Error::RowNotFound, _) => AppError::NotFound("Breed not found".to_string()),
+        (_, Some(UNIQUE_VIOLATION)) => {
+            AppError::Conflict("A breed with this slug or name already exists".to_string())
+        }
+        (_, Some(FOREIGN_KEY_VIOLATION)) => {
+            AppError::Conflict("Breed is still used by existing cats".to_string())
+        }
+        _ => err.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        api::test_helpers::{register, test_app, test_pool},
+        helpers::jwt::decode_jwt,
+    };
+    use actix_web::{http::StatusCode, test};
+    use serde_json::json;
+
+    #[actix_web::test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    async fn only_admins_manage_breeds_and_cats_use_them() {
+        let pool = test_pool().await;
+        let app = test_app(pool.clone()).await;
+
+        let user = register(&app).await;
+        let admin = register(&app).await;
+        sqlx::query("UPDATE users SET is_admin = TRUE WHERE id = $1")
+            .bind(decode_jwt(&admin).unwrap().id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let suffix = chrono::Utc::now().timestamp_micros();
+        let breed = json!({
+            "slug": format!("test-breed-{}", suffix),
+            "name": format!("Test {}", suffix),
+            "description": "Created by a test",
+        });
+
+        let req = test::TestRequest::post()
+            .uri("/v1/breed")
+            .insert_header(("Authorization", format!("Bearer {}", user)))
+            .set_json(&breed)
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        let req = test::TestRequest::post()
+            .uri("/v1/breed")
+            .insert_header(("Authorization", format!("Bearer {}", admin)))
+            .set_json(&breed)
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+
+        let req = test::TestRequest::post()
+            .uri("/v1/cat")
+            .insert_header(("Authorization", format!("Bearer {}", user)))
+            .set_json(json!({
+                "name": "Newcomer",
+                "race": breed["name"],
+                "sex": "male",
+                "ageInMonth": 3,
+                "description": "A cat of a brand new breed",
+                "imageUrls": ["https://example.com/cat.png"],
+            }))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+
+        let req = test::TestRequest::delete()
+            .uri(&format!("/v1/breed/{}", breed["slug"].as_str().unwrap()))
+            .insert_header(("Authorization", format!("Bearer {}", admin)))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+    }
+}
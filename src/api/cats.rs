@@ -1,15 +1,36 @@
 use crate::{
+    configs::{pagination::max_limit, retention::cat_restore_days},
     entities::{
-        cat::{CatResponse, CreateCatPayload, CreateCatRequest, CreateCatResponse, FilterCat},
-        ResponseWrapper,
+        cat::{
+            Cat, CatDetailResponse, CatResponse, CreateCatPayload, CreateCatRequest,
+            CreateCatResponse, FilterCat, Sex, UpdateCatRequest,
+        },
+        PaginationMeta, ResponseWrapper,
+    },
+    errors::{unknown_variant, AppError, FieldError, OrNotFound, INVALID_QUERY, INVALID_TYPE},
+    helpers::{
+        breeds::{breed_names, is_known_breed},
+        filters::NumericFilter,
+        pagination::Cursor,
+    },
+    middlewares::{
+        auth::Auth,
+        payload::{Json, Query},
+    },
+    repositories::{
+        cat::{
+            delete_cat, find_cat_detail, find_deleted_cat_owner, find_many_cats, find_one_cat,
+            insert_cat, partial_update_cat, restore_cat, update_cat,
+        },
+        r#match::has_active_matches,
     },
-    middlewares::auth::Auth,
-    repositories::cat::{find_many_cats, find_one_cat, insert_cat, update_cat},
     AppState,
 };
 use actix_web::{
-    delete, get, post, put, web,
-    web::{Data, Json},
+    delete, get,
+    http::header::{ETag, EntityTag, IfMatch},
+    patch, post, put, web,
+    web::Data,
     HttpResponse, Responder,
 };
 use validator::Validate;
@@ -18,53 +39,106 @@ use validator::Validate;
 async fn get_cats(
     state: Data<AppState>,
     Auth(user): Auth,
-    query: web::Query<FilterCat>,
-) -> impl Responder {
+    query: Query<FilterCat>,
+) -> Result<impl Responder, AppError> {
+    if let Some(race) = &query.race {
+        ensure_known_race(&state, race, INVALID_QUERY).await?;
+    }
+
+    let age_in_month = query
+        .age_in_month
+        .as_deref()
+        .map(str::parse::<NumericFilter>)
+        .transpose()
+        .map_err(|err| {
+            AppError::field(
+                "ageInMonth",
+                FieldError::new("invalid_filter", err.to_string()),
+            )
+        })?;
+
+    let cursor = query
+        .cursor
+        .as_deref()
+        .map(Cursor::decode)
+        .transpose()
+        .map_err(|err| {
+            AppError::field("cursor", FieldError::new("invalid_cursor", err.to_string()))
+        })?;
+
     let filter = FilterCat {
         id: query.id,
         search: query.search.clone(),
-        limit: query.limit,
-        offset: query.offset,
+        limit: query.limit.clamp(1, max_limit()),
+        offset: query.offset.max(0),
         race: query.race.clone(),
-        sex: query.sex.clone(),
+        sex: query.sex,
         age_in_month: query.age_in_month.clone(),
         has_matched: query.has_matched,
         owned: query.owned,
         user_id: Some(user.id),
+        cursor: None,
+        include_total: query.include_total,
+        sort_by: query.sort_by,
+        order: query.order,
     };
 
-    match find_many_cats(&state.db, filter).await {
-        Ok(cats) => {
-            let cats: Vec<CatResponse> = cats
-                .into_iter()
-                .map(|cat| {
-                    let has_matched = false;
-                    CatResponse {
-                        id: cat.id,
-                        name: cat.name,
-                        race: cat.race,
-                        sex: cat.sex,
-                        age_in_month: cat.age_in_month,
-                        description: cat.description,
-                        img_urls: cat.img_urls,
-                        created_at: cat.created_at,
-                        has_matched,
-                    }
-                })
-                .collect();
-
-            HttpResponse::Ok().json(ResponseWrapper::<Vec<CatResponse>> {
-                message: "Cats fetched successfully".to_string(),
-                data: Some(cats),
-            })
+    match (filter.keyset_sort(), &cursor) {
+        (None, Some(_)) => {
+            return Err(AppError::field(
+                "cursor",
+                FieldError::new(
+                    "invalid_cursor",
+                    "search results ranked by relevance are paged with offset, not cursor",
+                ),
+            ));
         }
-        Err(err) => {
-            return HttpResponse::InternalServerError().json(ResponseWrapper::<()> {
-                message: err.to_string(),
-                data: None,
-            });
+        (Some(sort_by), Some(cursor))
+            if filter.cursor_ordering().as_ref() != Some(&cursor.ordering)
+                || !sort_by.accepts(cursor) =>
+        {
+            return Err(AppError::field(
+                "cursor",
+                FieldError::new(
+                    "invalid_cursor",
+                    "cursor was issued for a different sortBy or order",
+                ),
+            ));
         }
+        _ => {}
     }
+
+    let page = find_many_cats(&state.db, filter, age_in_month, cursor).await?;
+
+    Ok(
+        HttpResponse::Ok().json(ResponseWrapper::<Vec<CatResponse>> {
+            message: "Cats fetched successfully".to_string(),
+            data: Some(page.cats),
+            meta: Some(PaginationMeta {
+                next_cursor: page.next_cursor.map(|cursor| cursor.encode()),
+                total: page.total,
+            }),
+        }),
+    )
+}
+
+#[get("/{id}")]
+async fn get_cat(
+    state: Data<AppState>,
+    _: Auth,
+    id: web::Path<i32>,
+) -> Result<impl Responder, AppError> {
+    let cat = find_cat_detail(&state.db, id.into_inner())
+        .await
+        .or_not_found("Cat not found")?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(ETag(cat_etag(cat.version)))
+        .json(ResponseWrapper::<CatDetailResponse> {
+            message: "Cat fetched successfully".to_string(),
+            data: Some(cat),
+            meta: None,
+        }))
 }
 
 #[post("")]
@@ -72,41 +146,30 @@ async fn create_cat(
     state: Data<AppState>,
     Auth(user): Auth,
     cat_payload: Json<CreateCatRequest>,
-) -> impl Responder {
-    match cat_payload.validate() {
-        Ok(_) => {
-            let cat = CreateCatPayload {
-                user_id: user.id,
-                name: cat_payload.name.clone(),
-                race: cat_payload.race.clone(),
-                sex: cat_payload.sex.clone(),
-                age_in_month: cat_payload.age_in_month.clone(),
-                description: cat_payload.description.clone(),
-                img_urls: cat_payload.img_urls.clone(),
-            };
+) -> Result<impl Responder, AppError> {
+    cat_payload.validate()?;
+    ensure_known_race(&state, &cat_payload.race, INVALID_TYPE).await?;
 
-            match insert_cat(&state.db, cat).await {
-                Ok(cat) => HttpResponse::Created().json(ResponseWrapper::<CreateCatResponse> {
-                    message: "Cat created successfully".to_string(),
-                    data: Some(CreateCatResponse {
-                        id: cat.id,
-                        created_at: cat.created_at,
-                    }),
-                }),
-                Err(err) => {
-                    return HttpResponse::InternalServerError().json(ResponseWrapper::<()> {
-                        message: err.to_string(),
-                        data: None,
-                    });
-                }
-            }
-        }
+    let cat_payload = cat_payload.into_inner();
+    let cat = CreateCatPayload {
+        user_id: user.id,
+        name: cat_payload.name,
+        race: cat_payload.race,
+        sex: cat_payload.sex,
+        age_in_month: cat_payload.age_in_month,
+        description: cat_payload.description,
+        img_urls: cat_payload.image_urls,
+    };
 
-        Err(err) => HttpResponse::BadRequest().json(ResponseWrapper::<()> {
-            message: err.to_string(),
-            data: None,
+    let cat = insert_cat(&state.db, cat).await?;
+
+    Ok(
+        HttpResponse::Created().json(ResponseWrapper::<CreateCatResponse> {
+            message: "Cat created successfully".to_string(),
+            data: Some(cat),
+            meta: None,
         }),
-    }
+    )
 }
 
 #[put("/{id}")]
@@ -114,73 +177,461 @@ async fn modify_cat(
     state: Data<AppState>,
     Auth(user): Auth,
     id: web::Path<i32>,
+    if_match: Option<web::Header<IfMatch>>,
     cat_payload: Json<CreateCatRequest>,
-) -> impl Responder {
-    match cat_payload.validate() {
-        Ok(_) => {
-            let update_cat_payload = CreateCatPayload {
-                user_id: user.id,
-                name: cat_payload.name.clone(),
-                race: cat_payload.race.clone(),
-                sex: cat_payload.sex.clone(),
-                age_in_month: cat_payload.age_in_month.clone(),
-                description: cat_payload.description.clone(),
-                img_urls: cat_payload.img_urls.clone(),
-            };
+) -> Result<impl Responder, AppError> {
+    cat_payload.validate()?;
+    ensure_known_race(&state, &cat_payload.race, INVALID_TYPE).await?;
 
-            match find_one_cat(&state.db, *id).await {
-                Ok(_) => match update_cat(&state.db, id.into_inner(), update_cat_payload).await {
-                    Ok(cat) => HttpResponse::Ok().json(ResponseWrapper::<CreateCatResponse> {
-                        message: "Cat updated successfully".to_string(),
-                        data: Some(CreateCatResponse {
-                            id: cat.id,
-                            created_at: cat.created_at,
-                        }),
-                    }),
-                    Err(err) => {
-                        return HttpResponse::InternalServerError().json(ResponseWrapper::<()> {
-                            message: err.to_string(),
-                            data: None,
-                        });
-                    }
-                },
-                Err(err) => {
-                    return HttpResponse::NotFound().json(ResponseWrapper::<()> {
-                        message: err.to_string(),
-                        data: None,
-                    });
-                }
-            }
-        }
-        Err(err) => {
-            return HttpResponse::BadRequest().json(ResponseWrapper::<()> {
-                message: err.to_string(),
-                data: None,
-            });
-        }
+    let cat_payload = cat_payload.into_inner();
+    let update_cat_payload = CreateCatPayload {
+        user_id: user.id,
+        name: cat_payload.name,
+        race: cat_payload.race,
+        sex: cat_payload.sex,
+        age_in_month: cat_payload.age_in_month,
+        description: cat_payload.description,
+        img_urls: cat_payload.image_urls,
+    };
+
+    let cat = find_owned_cat(&state, user.id, *id).await?;
+    let expected_version = check_if_match(if_match.as_deref(), &cat)?;
+    ensure_sex_change_allowed(&state, &cat, update_cat_payload.sex).await?;
+
+    let cat = update_cat(&state.db, cat.id, update_cat_payload, expected_version)
+        .await
+        .map_err(version_conflict)?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(ETag(cat_etag(cat.version)))
+        .json(ResponseWrapper::<CreateCatResponse> {
+            message: "Cat updated successfully".to_string(),
+            data: Some(cat),
+            meta: None,
+        }))
+}
+
+#[patch("/{id}")]
+async fn patch_cat(
+    state: Data<AppState>,
+    Auth(user): Auth,
+    id: web::Path<i32>,
+    if_match: Option<web::Header<IfMatch>>,
+    cat_payload: Json<UpdateCatRequest>,
+) -> Result<impl Responder, AppError> {
+    cat_payload.validate()?;
+
+    if cat_payload.is_empty() {
+        return Err(AppError::BadRequest(
+            "At least one field must be provided".to_string(),
+        ));
+    }
+
+    if let Some(race) = &cat_payload.race {
+        ensure_known_race(&state, race, INVALID_TYPE).await?;
+    }
+
+    let cat = find_owned_cat(&state, user.id, *id).await?;
+    let expected_version = check_if_match(if_match.as_deref(), &cat)?;
+
+    if let Some(sex) = cat_payload.sex {
+        ensure_sex_change_allowed(&state, &cat, sex).await?;
     }
+
+    let cat = partial_update_cat(
+        &state.db,
+        cat.id,
+        user.id,
+        cat_payload.into_inner(),
+        expected_version,
+    )
+    .await
+    .map_err(version_conflict)?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(ETag(cat_etag(cat.version)))
+        .json(ResponseWrapper::<CreateCatResponse> {
+            message: "Cat updated successfully".to_string(),
+            data: Some(cat),
+            meta: None,
+        }))
 }
 
 #[delete("/{id}")]
-async fn remove_cat(state: Data<AppState>, id: web::Path<i32>) -> impl Responder {
-    match find_one_cat(&state.db, *id).await {
-        Ok(_) => match crate::repositories::cat::delete_cat(&state.db, id.into_inner()).await {
-            Ok(_) => HttpResponse::Ok().json(ResponseWrapper::<()> {
-                message: "Cat deleted successfully".to_string(),
-                data: None,
-            }),
-            Err(err) => {
-                return HttpResponse::InternalServerError().json(ResponseWrapper::<()> {
-                    message: err.to_string(),
-                    data: None,
-                });
+async fn remove_cat(
+    state: Data<AppState>,
+    Auth(user): Auth,
+    id: web::Path<i32>,
+) -> Result<impl Responder, AppError> {
+    let cat = find_owned_cat(&state, user.id, *id).await?;
+    delete_cat(&state.db, cat.id, user.id)
+        .await
+        .or_not_found("Cat not found")?;
+
+    Ok(HttpResponse::Ok().json(ResponseWrapper::<()> {
+        message: "Cat deleted successfully".to_string(),
+        data: None,
+        meta: None,
+    }))
+}
+
+#[post("/{id}/restore")]
+async fn restore_deleted_cat(
+    state: Data<AppState>,
+    Auth(user): Auth,
+    id: web::Path<i32>,
+) -> Result<impl Responder, AppError> {
+    let owner_id = find_deleted_cat_owner(&state.db, *id)
+        .await
+        .or_not_found("Deleted cat not found")?;
+
+    if owner_id != user.id {
+        return Err(AppError::Forbidden("You do not own this cat".to_string()));
+    }
+
+    // The cat is still deleted, so a miss means the restore window has passed.
+    let cat = restore_cat(&state.db, id.into_inner(), cat_restore_days())
+        .await
+        .map_err(|err| match err {
+            sqlx::Error::RowNotFound => {
+                AppError::Gone("The restore period for this cat has expired".to_string())
+            }
+            err => err.into(),
+        })?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(ETag(cat_etag(cat.version)))
+        .json(ResponseWrapper::<CreateCatResponse> {
+            message: "Cat restored successfully".to_string(),
+            data: Some(cat),
+            meta: None,
+        }))
+}
+
+/// Loads a cat the caller is allowed to mutate, answering 404 when it does not
+/// exist and 403 when it belongs to someone else. The mutating queries filter
+/// on the owner as well; this check only picks the error.
+async fn find_owned_cat(state: &AppState, user_id: i32, id: i32) -> Result<Cat, AppError> {
+    let cat = find_one_cat(&state.db, id)
+        .await
+        .or_not_found("Cat not found")?;
+
+    if cat.user_id != user_id {
+        return Err(AppError::Forbidden("You do not own this cat".to_string()));
+    }
+
+    Ok(cat)
+}
+
+fn cat_etag(version: i32) -> EntityTag {
+    EntityTag::new_strong(version.to_string())
+}
+
+/// Checks `If-Match` against the cat's current version. Returns the version the
+/// update must still see, or `None` when the request is unconditional.
+fn check_if_match(if_match: Option<&IfMatch>, cat: &Cat) -> Result<Option<i32>, AppError> {
+    match if_match {
+        // A missing header parses as an empty item list.
+        None | Some(IfMatch::Any) => Ok(None),
+        Some(IfMatch::Items(tags)) if tags.is_empty() => Ok(None),
+        Some(IfMatch::Items(tags)) => {
+            let current = cat_etag(cat.version);
+            if tags.iter().any(|tag| tag.strong_eq(&current)) {
+                Ok(Some(cat.version))
+            } else {
+                Err(precondition_failed())
             }
+        }
+    }
+}
+
+fn precondition_failed() -> AppError {
+    AppError::PreconditionFailed(
+        "Cat was modified by someone else, fetch it again and retry".to_string(),
+    )
+}
+
+/// A versioned update that matches no row lost the race to another writer.
+fn version_conflict(err: sqlx::Error) -> AppError {
+    match err {
+        sqlx::Error::RowNotFound => precondition_failed(),
+        err => err.into(),
+    }
+}
+
+/// Rejects a race that is not in the breed catalogue with the error an enum
+/// would give, `code` saying whether it came from the body or the query.
+async fn ensure_known_race(state: &AppState, race: &str, code: &str) -> Result<(), AppError> {
+    if is_known_breed(&state.db, race).await? {
+        return Ok(());
+    }
+
+    let breeds = breed_names(&state.db).await?;
+    Err(unknown_variant("race", code, race, &breeds))
+}
+
+/// Refuses to change the sex of a cat that has pending or approved match
+/// requests, since those were made on the basis of its current sex.
+async fn ensure_sex_change_allowed(state: &AppState, cat: &Cat, sex: Sex) -> Result<(), AppError> {
+    if cat.sex == sex || !has_active_matches(&state.db, cat.id).await? {
+        return Ok(());
+    }
+
+    Err(AppError::BadRequest(
+        "Cannot change the sex of a cat that has pending or approved match requests".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        api::test_helpers::{
+            call, call_json, cat_body, create_cat, register, request_match, test_app, test_pool,
         },
-        Err(err) => {
-            return HttpResponse::NotFound().json(ResponseWrapper::<()> {
-                message: err.to_string(),
-                data: None,
-            });
+        helpers::jwt::decode_jwt,
+        repositories::cat::{delete_cat, partial_update_cat, update_cat},
+    };
+    use actix_web::{http::StatusCode, test::TestRequest};
+    use serde_json::json;
+
+    #[derive(Clone, Copy)]
+    enum Caller {
+        Owner,
+        Intruder,
+        Anonymous,
+    }
+
+    type Step = (fn(i64) -> TestRequest, Caller, StatusCode);
+
+    fn put(id: i64) -> TestRequest {
+        TestRequest::put()
+            .uri(&format!("/v1/cat/{}", id))
+            .set_json(cat_body("male"))
+    }
+
+    fn patch(id: i64) -> TestRequest {
+        TestRequest::patch()
+            .uri(&format!("/v1/cat/{}", id))
+            .set_json(json!({ "name": "Renamed" }))
+    }
+
+    fn patch_version_1(id: i64) -> TestRequest {
+        patch(id).insert_header(("If-Match", "\"1\""))
+    }
+
+    fn get(id: i64) -> TestRequest {
+        TestRequest::get().uri(&format!("/v1/cat/{}", id))
+    }
+
+    fn delete(id: i64) -> TestRequest {
+        TestRequest::delete().uri(&format!("/v1/cat/{}", id))
+    }
+
+    fn restore(id: i64) -> TestRequest {
+        TestRequest::post().uri(&format!("/v1/cat/{}/restore", id))
+    }
+
+    /// Runs `steps` in order against a fresh male cat, checking each status.
+    async fn run_steps(steps: &[Step]) {
+        let app = test_app(test_pool().await).await;
+
+        let owner = register(&app).await;
+        let intruder = register(&app).await;
+        let cat_id = create_cat(&app, &owner, "male").await;
+
+        for (index, (request, caller, expected)) in steps.iter().enumerate() {
+            let token = match caller {
+                Caller::Owner => Some(owner.as_str()),
+                Caller::Intruder => Some(intruder.as_str()),
+                Caller::Anonymous => None,
+            };
+            let res = call(&app, request(cat_id), token).await;
+            assert_eq!(res.status(), *expected, "step {}", index);
+        }
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    async fn only_the_owner_can_modify_a_cat() {
+        run_steps(&[
+            (put, Caller::Intruder, StatusCode::FORBIDDEN),
+            (patch, Caller::Intruder, StatusCode::FORBIDDEN),
+            (delete, Caller::Intruder, StatusCode::FORBIDDEN),
+            (put, Caller::Anonymous, StatusCode::UNAUTHORIZED),
+            (patch, Caller::Anonymous, StatusCode::UNAUTHORIZED),
+            (delete, Caller::Anonymous, StatusCode::UNAUTHORIZED),
+            (get, Caller::Owner, StatusCode::OK),
+            (put, Caller::Owner, StatusCode::OK),
+            (patch, Caller::Owner, StatusCode::OK),
+            (delete, Caller::Owner, StatusCode::OK),
+        ])
+        .await;
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    async fn mutating_queries_check_the_owner_themselves() {
+        let pool = test_pool().await;
+        let app = test_app(pool.clone()).await;
+
+        let owner = register(&app).await;
+        let intruder_id = decode_jwt(&register(&app).await).unwrap().id;
+        let cat_id = create_cat(&app, &owner, "male").await;
+        let id = cat_id as i32;
+
+        let mut replacement = cat_body("female");
+        replacement["userId"] = json!(intruder_id);
+        let replacement = serde_json::from_value(replacement).unwrap();
+        let patch = serde_json::from_value(json!({ "name": "Stolen" })).unwrap();
+
+        let results = [
+            update_cat(&pool, id, replacement, None).await.map(drop),
+            partial_update_cat(&pool, id, intruder_id, patch, None)
+                .await
+                .map(drop),
+            delete_cat(&pool, id, intruder_id).await,
+        ];
+        for (index, result) in results.into_iter().enumerate() {
+            assert!(
+                matches!(result, Err(sqlx::Error::RowNotFound)),
+                "query {}",
+                index
+            );
+        }
+
+        let (status, body) = call_json(&app, get(cat_id), Some(&owner)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["name"], "Whiskers");
+        assert_eq!(body["data"]["sex"], "male");
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    async fn stale_if_match_is_rejected() {
+        run_steps(&[
+            (patch_version_1, Caller::Owner, StatusCode::OK),
+            (
+                patch_version_1,
+                Caller::Owner,
+                StatusCode::PRECONDITION_FAILED,
+            ),
+        ])
+        .await;
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    async fn deleted_cat_can_be_restored_by_owner() {
+        run_steps(&[
+            (delete, Caller::Owner, StatusCode::OK),
+            (get, Caller::Owner, StatusCode::NOT_FOUND),
+            (patch, Caller::Owner, StatusCode::NOT_FOUND),
+            (restore, Caller::Intruder, StatusCode::FORBIDDEN),
+            (restore, Caller::Owner, StatusCode::OK),
+            (get, Caller::Owner, StatusCode::OK),
+            (restore, Caller::Owner, StatusCode::NOT_FOUND),
+        ])
+        .await;
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    async fn updates_bump_the_etag() {
+        let app = test_app(test_pool().await).await;
+
+        let owner = register(&app).await;
+        let cat_id = create_cat(&app, &owner, "male").await;
+
+        for (request, etag) in [
+            (get as fn(i64) -> TestRequest, "\"1\""),
+            (patch, "\"2\""),
+            (put, "\"3\""),
+        ] {
+            let res = call(&app, request(cat_id), Some(&owner)).await;
+            assert_eq!(res.status(), StatusCode::OK);
+            assert_eq!(res.headers().get("ETag").unwrap(), etag);
+        }
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    async fn unknown_races_are_rejected_like_unknown_sexes() {
+        let app = test_app(test_pool().await).await;
+
+        let owner = register(&app).await;
+        let cat_id = create_cat(&app, &owner, "male").await;
+
+        let mut bad_race = cat_body("male");
+        bad_race["race"] = json!("Dragon");
+        let mut bad_sex = cat_body("male");
+        bad_sex["sex"] = json!("other");
+
+        let cases = [
+            (
+                TestRequest::post().uri("/v1/cat").set_json(&bad_race),
+                "race",
+                "invalid_type",
+            ),
+            (
+                TestRequest::post().uri("/v1/cat").set_json(&bad_sex),
+                "sex",
+                "invalid_type",
+            ),
+            (put(cat_id).set_json(&bad_race), "race", "invalid_type"),
+            (
+                patch(cat_id).set_json(json!({ "race": "Dragon" })),
+                "race",
+                "invalid_type",
+            ),
+            (
+                TestRequest::get().uri("/v1/cat?race=Dragon"),
+                "race",
+                "invalid_query",
+            ),
+            (
+                TestRequest::get().uri("/v1/cat?sex=other"),
+                "sex",
+                "invalid_query",
+            ),
+        ];
+        for (request, field, code) in cases {
+            let (status, body) = call_json(&app, request, Some(&owner)).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", field);
+
+            let error = &body["errors"][field][0];
+            assert_eq!(error["code"], code, "{}", field);
+            assert!(
+                error["message"]
+                    .as_str()
+                    .unwrap()
+                    .starts_with("unknown variant `"),
+                "{}",
+                error
+            );
+        }
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    async fn cannot_change_sex_with_pending_match() {
+        let app = test_app(test_pool().await).await;
+
+        let issuer = register(&app).await;
+        let receiver = register(&app).await;
+        let user_cat_id = create_cat(&app, &issuer, "male").await;
+        let match_cat_id = create_cat(&app, &receiver, "female").await;
+        request_match(&app, &issuer, user_cat_id, match_cat_id).await;
+
+        for request in [
+            TestRequest::put()
+                .uri(&format!("/v1/cat/{}", user_cat_id))
+                .set_json(cat_body("female")),
+            TestRequest::patch()
+                .uri(&format!("/v1/cat/{}", user_cat_id))
+                .set_json(json!({ "sex": "female" })),
+        ] {
+            let (status, _) = call_json(&app, request, Some(&issuer)).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
         }
     }
 }
@@ -0,0 +1,12 @@
+use crate::{configs::jwt_keys::jwt_keys, errors::AppError};
+use actix_web::{get, HttpResponse, Responder};
+
+/// Public verification keys, in the standard JWK set format other services
+/// expect rather than our `ResponseWrapper`.
+#[get("/.well-known/jwks.json")]
+async fn get_jwks() -> Result<impl Responder, AppError> {
+    let keys =
+        jwt_keys().ok_or_else(|| AppError::Internal("JWT keys are not initialised".to_string()))?;
+
+    Ok(HttpResponse::Ok().json(&keys.jwks))
+}
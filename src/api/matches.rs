@@ -0,0 +1,362 @@
+use crate::{
+    entities::{
+        r#match::{
+            CatMatch, CreateMatchRequest, CreateMatchResponse, MatchDecisionRequest, MatchResponse,
+            MatchStatus,
+        },
+        ResponseWrapper,
+    },
+    errors::{sqlstate, AppError, OrNotFound, UNIQUE_VIOLATION},
+    middlewares::{auth::Auth, payload::Json},
+    repositories::{
+        cat::find_one_cat,
+        r#match::{approve_match, find_many_matches, find_one_match, insert_match, reject_match},
+    },
+    AppState,
+};
+use actix_web::{get, post, web::Data, HttpResponse, Responder};
+use validator::Validate;
+
+#[get("/match")]
+async fn get_matches(state: Data<AppState>, Auth(user): Auth) -> Result<impl Responder, AppError> {
+    let matches = find_many_matches(&state.db, user.id).await?;
+
+    Ok(
+        HttpResponse::Ok().json(ResponseWrapper::<Vec<MatchResponse>> {
+            message: "Match requests fetched successfully".to_string(),
+            data: Some(matches),
+            meta: None,
+        }),
+    )
+}
+
+#[post("/match")]
+async fn create_match(
+    state: Data<AppState>,
+    Auth(user): Auth,
+    match_payload: Json<CreateMatchRequest>,
+) -> Result<impl Responder, AppError> {
+    match_payload.validate()?;
+
+    let user_cat = find_one_cat(&state.db, match_payload.user_cat_id)
+        .await
+        .or_not_found("User cat not found")?;
+
+    if user_cat.user_id != user.id {
+        return Err(AppError::NotFound("User cat not found".to_string()));
+    }
+
+    let match_cat = find_one_cat(&state.db, match_payload.match_cat_id)
+        .await
+        .or_not_found("Match cat not found")?;
+
+    if match_cat.user_id == user.id {
+        return Err(AppError::BadRequest(
+            "Cannot match cats from the same owner".to_string(),
+        ));
+    }
+
+    if user_cat.sex == match_cat.sex {
+        return Err(AppError::BadRequest(
+            "Cats must have opposite sexes".to_string(),
+        ));
+    }
+
+    if let Some(cat) = [&user_cat, &match_cat]
+        .into_iter()
+        .find(|cat| cat.has_matched)
+    {
+        return Err(AppError::BadRequest(format!(
+            "Cat {} is already matched",
+            cat.id
+        )));
+    }
+
+    let cat_match = insert_match(&state.db, user.id, match_payload.into_inner())
+        .await
+        .map_err(|err| match sqlstate(&err).as_deref() {
+            Some(UNIQUE_VIOLATION) => AppError::Conflict(
+                "A pending match request between these cats already exists".to_string(),
+            ),
+            _ => err.into(),
+        })?;
+
+    Ok(
+        HttpResponse::Created().json(ResponseWrapper::<CreateMatchResponse> {
+            message: "Match request created successfully".to_string(),
+            data: Some(cat_match),
+            meta: None,
+        }),
+    )
+}
+
+/// Loads a match request that the caller may decide on: it must exist, target
+/// one of the caller's cats and still be pending.
+async fn find_decidable_match(
+    state: &AppState,
+    user_id: i32,
+    match_id: i32,
+) -> Result<CatMatch, AppError> {
+    let not_found = || AppError::NotFound("Match request not found".to_string());
+
+    let cat_match = find_one_match(&state.db, match_id)
+        .await
+        .or_not_found("Match request not found")?;
+
+    let match_cat_id = cat_match.match_cat_id.ok_or_else(not_found)?;
+    let match_cat = find_one_cat(&state.db, match_cat_id)
+        .await
+        .or_not_found("Match request not found")?;
+
+    if match_cat.user_id != user_id {
+        return Err(not_found());
+    }
+
+    if cat_match.deleted_at.is_some() {
+        return Err(AppError::BadRequest(
+            "Match request was withdrawn because one of the cats was deleted".to_string(),
+        ));
+    }
+
+    if cat_match.status != MatchStatus::Pending {
+        return Err(already_decided());
+    }
+
+    Ok(cat_match)
+}
+
+fn already_decided() -> AppError {
+    AppError::BadRequest("Match request has already been decided".to_string())
+}
+
+/// Another request may decide the match between our check and the update.
+fn decision_error(err: sqlx::Error) -> AppError {
+    match err {
+        sqlx::Error::RowNotFound => already_decided(),
+        err => err.into(),
+    }
+}
+
+#[post("/match/approve")]
+async fn approve_match_request(
+    state: Data<AppState>,
+    Auth(user): Auth,
+    payload: Json<MatchDecisionRequest>,
+) -> Result<impl Responder, AppError> {
+    find_decidable_match(&state, user.id, payload.match_id).await?;
+
+    let cat_match = approve_match(&state.db, payload.match_id)
+        .await
+        .map_err(decision_error)?
+        .ok_or_else(|| AppError::BadRequest("One of the cats is already matched".to_string()))?;
+
+    Ok(HttpResponse::Ok().json(ResponseWrapper::<CatMatch> {
+        message: "Match request approved successfully".to_string(),
+        data: Some(cat_match),
+        meta: None,
+    }))
+}
+
+#[post("/match/reject")]
+async fn reject_match_request(
+    state: Data<AppState>,
+    Auth(user): Auth,
+    payload: Json<MatchDecisionRequest>,
+) -> Result<impl Responder, AppError> {
+    find_decidable_match(&state, user.id, payload.match_id).await?;
+
+    let cat_match = reject_match(&state.db, payload.match_id)
+        .await
+        .map_err(decision_error)?;
+
+    Ok(HttpResponse::Ok().json(ResponseWrapper::<CatMatch> {
+        message: "Match request rejected successfully".to_string(),
+        data: Some(cat_match),
+        meta: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::test_helpers::{
+        call, call_json, create_cat, register, request_match, test_app, test_pool,
+    };
+    use actix_web::{http::StatusCode, test::TestRequest};
+    use serde_json::json;
+
+    /// A request about the cat with the given id.
+    type Action = fn(i64) -> TestRequest;
+
+    fn create_match(user_cat_id: i64, match_cat_id: i64) -> TestRequest {
+        TestRequest::post().uri("/v1/cat/match").set_json(json!({
+            "userCatId": user_cat_id,
+            "matchCatId": match_cat_id,
+            "message": "Hello again",
+        }))
+    }
+
+    fn approve(match_id: i64) -> TestRequest {
+        TestRequest::post()
+            .uri("/v1/cat/match/approve")
+            .set_json(json!({ "matchId": match_id }))
+    }
+
+    fn list_matches() -> TestRequest {
+        TestRequest::get().uri("/v1/cat/match")
+    }
+
+    fn get_cat(id: i64) -> TestRequest {
+        TestRequest::get().uri(&format!("/v1/cat/{}", id))
+    }
+
+    fn delete_cat(id: i64) -> TestRequest {
+        TestRequest::delete().uri(&format!("/v1/cat/{}", id))
+    }
+
+    fn restore_cat(id: i64) -> TestRequest {
+        TestRequest::post().uri(&format!("/v1/cat/{}/restore", id))
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    async fn deleting_a_matched_cat_frees_its_partner() {
+        let app = test_app(test_pool().await).await;
+
+        let owner = register(&app).await;
+        let partner_owner = register(&app).await;
+        let cat_id = create_cat(&app, &owner, "male").await;
+        let partner_id = create_cat(&app, &partner_owner, "female").await;
+
+        let match_id = request_match(&app, &owner, cat_id, partner_id).await;
+        let res = call(&app, approve(match_id), Some(&partner_owner)).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // The owner's action on their cat, then whether the partner is matched.
+        let steps: [(Option<Action>, bool); 3] = [
+            (None, true),
+            (Some(delete_cat), false),
+            (Some(restore_cat), true),
+        ];
+
+        for (index, (action, partner_matched)) in steps.into_iter().enumerate() {
+            if let Some(action) = action {
+                let res = call(&app, action(cat_id), Some(&owner)).await;
+                assert_eq!(res.status(), StatusCode::OK, "step {}", index);
+            }
+
+            let (_, body) = call_json(&app, get_cat(partner_id), Some(&partner_owner)).await;
+            assert_eq!(
+                body["data"]["hasMatched"], partner_matched,
+                "step {}",
+                index
+            );
+        }
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    async fn only_one_pending_request_per_pair() {
+        let app = test_app(test_pool().await).await;
+
+        let owner = register(&app).await;
+        let partner_owner = register(&app).await;
+        let cat_id = create_cat(&app, &owner, "male").await;
+        let partner_id = create_cat(&app, &partner_owner, "female").await;
+
+        let cases = [
+            (&owner, cat_id, partner_id, StatusCode::CREATED),
+            (&owner, cat_id, partner_id, StatusCode::CONFLICT),
+            (&partner_owner, partner_id, cat_id, StatusCode::CREATED),
+        ];
+        for (token, user_cat_id, match_cat_id, expected) in cases {
+            let req = create_match(user_cat_id, match_cat_id);
+            let res = call(&app, req, Some(token)).await;
+            assert_eq!(
+                res.status(),
+                expected,
+                "{} -> {}",
+                user_cat_id,
+                match_cat_id
+            );
+        }
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    async fn cannot_approve_a_request_for_a_matched_cat() {
+        let pool = test_pool().await;
+        let app = test_app(pool.clone()).await;
+
+        let owner = register(&app).await;
+        let partner_owner = register(&app).await;
+        let cat_id = create_cat(&app, &owner, "male").await;
+        let partner_id = create_cat(&app, &partner_owner, "female").await;
+        let match_id = request_match(&app, &owner, cat_id, partner_id).await;
+
+        // As if another approval for the partner committed after our checks.
+        sqlx::query("UPDATE cats SET has_matched = TRUE WHERE id = $1")
+            .bind(partner_id as i32)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let res = call(&app, approve(match_id), Some(&partner_owner)).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let (_, body) = call_json(&app, get_cat(cat_id), Some(&owner)).await;
+        assert_eq!(body["data"]["hasMatched"], false);
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    async fn restoring_a_match_rejects_requests_left_over_from_the_delete() {
+        let app = test_app(test_pool().await).await;
+
+        // Whether the partner approves another cat while ours is deleted.
+        for partner_matched_elsewhere in [false, true] {
+            let owner = register(&app).await;
+            let partner_owner = register(&app).await;
+            let rival_owner = register(&app).await;
+            let cat_id = create_cat(&app, &owner, "male").await;
+            let partner_id = create_cat(&app, &partner_owner, "female").await;
+            let rival_id = create_cat(&app, &rival_owner, "male").await;
+
+            let match_id = request_match(&app, &owner, cat_id, partner_id).await;
+            let res = call(&app, approve(match_id), Some(&partner_owner)).await;
+            assert_eq!(res.status(), StatusCode::OK);
+
+            let res = call(&app, delete_cat(cat_id), Some(&owner)).await;
+            assert_eq!(res.status(), StatusCode::OK);
+
+            let rival_match_id = request_match(&app, &rival_owner, rival_id, partner_id).await;
+            if partner_matched_elsewhere {
+                let res = call(&app, approve(rival_match_id), Some(&partner_owner)).await;
+                assert_eq!(res.status(), StatusCode::OK);
+            }
+
+            let res = call(&app, restore_cat(cat_id), Some(&owner)).await;
+            assert_eq!(res.status(), StatusCode::OK);
+
+            let (_, body) = call_json(&app, get_cat(cat_id), Some(&owner)).await;
+            assert_eq!(body["data"]["hasMatched"], !partner_matched_elsewhere);
+
+            // The rival's request is either the partner's match or a leftover.
+            let res = call(&app, approve(rival_match_id), Some(&partner_owner)).await;
+            assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+            let (_, body) = call_json(&app, list_matches(), Some(&rival_owner)).await;
+            let rival_match = body["data"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|m| m["id"] == rival_match_id)
+                .unwrap();
+            let expected = if partner_matched_elsewhere {
+                "approved"
+            } else {
+                "rejected"
+            };
+            assert_eq!(rival_match["status"], expected);
+        }
+    }
+}
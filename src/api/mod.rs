@@ -3,29 +3,59 @@ use actix_web::{web, App, HttpServer};
 use sqlx::Pool;
 use sqlx::Postgres;
 
+use crate::errors::path_error_handler;
+use crate::middlewares::payload::JSON_LIMIT;
 use crate::AppState;
 
+pub mod breeds;
 pub mod cats;
+pub mod jwks;
+pub mod matches;
 pub mod users;
 
+#[cfg(test)]
+mod test_helpers;
+
 pub fn user_path() -> actix_web::Scope {
     web::scope("user")
         .service(users::register_user)
         .service(users::login_user)
+        .service(users::refresh_user_token)
+        .service(users::logout_user)
+        .service(users::get_profile)
+        .service(users::update_profile)
+}
+
+pub fn breed_path() -> actix_web::Scope {
+    web::scope("breed")
+        .service(breeds::get_breeds)
+        .service(breeds::create_breed)
+        .service(breeds::modify_breed)
+        .service(breeds::remove_breed)
 }
 
 pub fn cat_path() -> actix_web::Scope {
     web::scope("cat")
+        .service(matches::get_matches)
+        .service(matches::create_match)
+        .service(matches::approve_match_request)
+        .service(matches::reject_match_request)
         .service(cats::get_cats)
+        .service(cats::get_cat)
         .service(cats::create_cat)
         .service(cats::modify_cat)
+        .service(cats::patch_cat)
         .service(cats::remove_cat)
+        .service(cats::restore_deleted_cat)
 }
 
 pub fn base_path() -> actix_web::Scope {
     web::scope("v1")
+        .app_data(web::PathConfig::default().error_handler(path_error_handler))
+        .app_data(web::PayloadConfig::new(JSON_LIMIT))
         .service(web::resource("/").to(|| async { "Hello, world!" }))
         .service(user_path())
+        .service(breed_path())
         .service(cat_path())
 }
 
@@ -36,6 +66,7 @@ pub async fn run_server(pool: Pool<Postgres>) -> std::io::Result<()> {
         App::new()
             .wrap(logger)
             .app_data(web::Data::new(AppState { db: pool.clone() }))
+            .service(jwks::get_jwks)
             .service(base_path())
     })
     .bind(("0.0.0.0", 8080))?
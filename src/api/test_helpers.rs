@@ -0,0 +1,171 @@
+use crate::{api::base_path, configs::jwt_keys::init_jwt_keys, AppState};
+use actix_http::Request;
+use actix_web::{
+    body::MessageBody,
+    dev::{Service, ServiceResponse},
+    http::StatusCode,
+    test, web, App,
+};
+use serde_json::{json, Value};
+use sqlx::{postgres::PgPoolOptions, PgPool};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+static USER_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// Connects to `TEST_DATABASE_URL` and runs the migrations. Tests using it are
+/// `#[ignore]`d and run with `cargo test -- --include-ignored` against a
+/// database, as the `test` CI workflow does, so a missing variable is a
+/// failure rather than a silent pass.
+pub async fn test_pool() -> PgPool {
+    dotenv::dotenv().ok();
+
+    let url = std::env::var("TEST_DATABASE_URL")
+        .expect("TEST_DATABASE_URL must be set to run database tests");
+
+    if std::env::var("JWT_SECRET").is_err() {
+        std::env::set_var("JWT_SECRET", "test-secret");
+    }
+    init_jwt_keys().expect("Failed to load JWT keys");
+
+    let pool = PgPoolOptions::new()
+        .max_connections(5)
+        .connect(&url)
+        .await
+        .expect("Failed to connect to the test database");
+
+    sqlx::migrate!()
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations");
+
+    pool
+}
+
+/// The full `/v1` API backed by `pool`.
+pub async fn test_app(
+    pool: PgPool,
+) -> impl Service<Request, Response = ServiceResponse<impl MessageBody>, Error = actix_web::Error> {
+    test::init_service(
+        App::new()
+            .app_data(web::Data::new(AppState { db: pool }))
+            .service(base_path()),
+    )
+    .await
+}
+
+/// Registers a fresh user and returns the `data` of the response: their
+/// email and both tokens.
+pub async fn register_user<S, B>(app: &S) -> Value
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let email = format!(
+        "user{}-{}@test.com",
+        chrono::Utc::now().timestamp_micros(),
+        USER_COUNTER.fetch_add(1, Ordering::SeqCst)
+    );
+    let req = test::TestRequest::post()
+        .uri("/v1/user/register")
+        .set_json(json!({ "email": email, "name": "Test User", "password": "secret" }))
+        .to_request();
+    let mut body: Value = test::call_and_read_body_json(app, req).await;
+    body["data"]["email"] = json!(email);
+
+    body["data"].take()
+}
+
+/// Registers a fresh user and returns their access token.
+pub async fn register<S, B>(app: &S) -> String
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    register_user(app).await["accessToken"]
+        .as_str()
+        .expect("registration should return a token")
+        .to_string()
+}
+
+/// A valid cat payload of the given `sex`.
+pub fn cat_body(sex: &str) -> Value {
+    json!({
+        "name": "Whiskers",
+        "race": "Persian",
+        "sex": sex,
+        "ageInMonth": 12,
+        "description": "A test cat",
+        "imageUrls": ["https://example.com/cat.png"],
+    })
+}
+
+/// Creates a cat owned by `token` and returns its id.
+pub async fn create_cat<S, B>(app: &S, token: &str, sex: &str) -> i64
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let req = test::TestRequest::post()
+        .uri("/v1/cat")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(cat_body(sex))
+        .to_request();
+    let body: Value = test::call_and_read_body_json(app, req).await;
+
+    body["data"]["id"]
+        .as_i64()
+        .expect("cat creation should return an id")
+}
+
+/// Sends a match request from `user_cat_id` to `match_cat_id` as `token` and
+/// returns the request's id.
+pub async fn request_match<S, B>(app: &S, token: &str, user_cat_id: i64, match_cat_id: i64) -> i64
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let req = test::TestRequest::post()
+        .uri("/v1/cat/match")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({
+            "userCatId": user_cat_id,
+            "matchCatId": match_cat_id,
+            "message": "Let them meet",
+        }))
+        .to_request();
+    let body: Value = test::call_and_read_body_json(app, req).await;
+
+    body["data"]["id"]
+        .as_i64()
+        .expect("match request should return an id")
+}
+
+/// Sends `req` as the holder of `token`, or anonymously when it is `None`.
+pub async fn call<S, B>(app: &S, req: test::TestRequest, token: Option<&str>) -> ServiceResponse<B>
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let req = match token {
+        Some(token) => req.insert_header(("Authorization", format!("Bearer {}", token))),
+        None => req,
+    };
+
+    test::call_service(app, req.to_request()).await
+}
+
+/// Like [`call`], also reading the JSON body.
+pub async fn call_json<S, B>(
+    app: &S,
+    req: test::TestRequest,
+    token: Option<&str>,
+) -> (StatusCode, Value)
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let res = call(app, req, token).await;
+    let status = res.status();
+
+    (status, test::read_body_json(res).await)
+}
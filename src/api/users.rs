@@ -1,168 +1,472 @@
 use crate::{
+    configs::auth::refresh_token_days,
     entities::{
-        user::{CreateUser, FilterUser, LoginUser, UserResponse},
+        refresh_token::{LogoutRequest, RefreshRotation, RefreshTokenRequest, TokenResponse},
+        user::{
+            CreateUser, FilterUser, LoginUser, ProfileResponse, UpdateUserRequest, UserResponse,
+        },
         ResponseWrapper,
     },
+    errors::{sqlstate, AppError, OrNotFound, UNIQUE_VIOLATION},
     helpers::{
         jwt::get_jwt,
         passwords::{hash_password, verify_password},
+        refresh_tokens::{generate_refresh_token, hash_refresh_token, random_token},
+        revocations::{mark_revoked, revoke},
+    },
+    middlewares::{
+        auth::Auth,
+        payload::{Json, OptionalJson},
+    },
+    repositories::{
+        refresh_token::{
+            insert_refresh_token, revoke_refresh_token_family, revoke_user_refresh_tokens,
+            rotate_refresh_token,
+        },
+        revoked_token::insert_revoked_token,
+        user::{find_one_user, insert_user, update_user},
     },
-    repositories::user::{find_one_user, insert_user},
     AppState,
 };
-use actix_web::{
-    post,
-    web::{Data, Json},
-    HttpResponse, Responder,
-};
+use actix_web::{get, patch, post, web::Data, HttpResponse, Responder};
 use validator::Validate;
 
 #[post("/register")]
-async fn register_user(state: Data<AppState>, user: Json<CreateUser>) -> impl Responder {
-    match user.validate() {
-        Ok(_) => {
-            let hashed_password = match hash_password(&user.password) {
-                Ok(password) => password,
-                Err(err) => {
-                    log::error!("Password hashing error: {}", err);
-                    return HttpResponse::InternalServerError().json(ResponseWrapper::<()> {
-                        message: err.to_string(),
-                        data: None,
-                    });
-                }
-            };
-
-            let user = CreateUser {
-                name: user.name.clone(),
-                email: user.email.clone(),
-                password: hashed_password,
-            };
-
-            match insert_user(&state.db, user).await {
-                Ok(user) => {
-                    let token = match get_jwt(user.email.clone(), user.id) {
-                        Ok(token) => token,
-                        Err(err) => {
-                            log::error!("JWT generation error: {}", err);
-                            return HttpResponse::InternalServerError().json(
-                                ResponseWrapper::<()> {
-                                    message: err,
-                                    data: None,
-                                },
-                            );
-                        }
-                    };
-
-                    HttpResponse::Created().json(ResponseWrapper::<UserResponse> {
-                        message: "User registered successfully".to_string(),
-                        data: Some(UserResponse {
-                            name: user.name,
-                            email: user.email,
-                            access_token: token,
-                        }),
-                    })
-                }
-                Err(err) => {
-                    log::error!("Database insertion error: {}", err);
-                    HttpResponse::InternalServerError().json(ResponseWrapper::<()> {
-                        message: err.to_string(),
-                        data: None,
-                    })
-                }
-            }
+async fn register_user(
+    state: Data<AppState>,
+    user: Json<CreateUser>,
+) -> Result<impl Responder, AppError> {
+    user.validate()?;
+
+    let hashed_password = hash_password(&user.password)
+        .map_err(|err| AppError::Internal(format!("Password hashing error: {}", err)))?;
+
+    let user = CreateUser {
+        name: user.name.clone(),
+        email: user.email.clone(),
+        password: hashed_password,
+    };
+
+    let user = insert_user(&state.db, user).await.map_err(email_conflict)?;
+    let token = issue_access_token(&user.email, user.id)?;
+    let refresh_token = issue_refresh_token(&state, user.id).await?;
+
+    Ok(
+        HttpResponse::Created().json(ResponseWrapper::<UserResponse> {
+            message: "User registered successfully".to_string(),
+            data: Some(UserResponse {
+                name: user.name,
+                email: user.email,
+                access_token: token,
+                refresh_token,
+                updated_at: user.updated_at,
+            }),
+            meta: None,
+        }),
+    )
+}
+
+#[post("/login")]
+async fn login_user(
+    state: Data<AppState>,
+    user_payload: Json<LoginUser>,
+) -> Result<impl Responder, AppError> {
+    user_payload.validate()?;
+
+    let user_filter = FilterUser {
+        id: Some(0),
+        name: None,
+        email: Some(user_payload.email.clone()),
+    };
+
+    let user = find_one_user(&state.db, user_filter)
+        .await
+        .or_not_found("User not found")?;
+
+    verify_password(&user_payload.password, &user.password)
+        .map_err(|_| AppError::Unauthorized("Invalid password".to_string()))?;
+
+    let token = issue_access_token(&user.email, user.id)?;
+    let refresh_token = issue_refresh_token(&state, user.id).await?;
+
+    Ok(HttpResponse::Ok().json(ResponseWrapper::<UserResponse> {
+        message: "User logged in successfully".to_string(),
+        data: Some(UserResponse {
+            name: user.name,
+            email: user.email,
+            access_token: token,
+            refresh_token,
+            updated_at: user.updated_at,
+        }),
+        meta: None,
+    }))
+}
+
+#[post("/refresh")]
+async fn refresh_user_token(
+    state: Data<AppState>,
+    payload: Json<RefreshTokenRequest>,
+) -> Result<impl Responder, AppError> {
+    payload.validate()?;
+
+    let refresh_token = generate_refresh_token();
+    let rotation = rotate_refresh_token(
+        &state.db,
+        &hash_refresh_token(&payload.refresh_token),
+        &hash_refresh_token(&refresh_token),
+        refresh_token_days(),
+    )
+    .await?;
+
+    match rotation {
+        RefreshRotation::Rotated { user_id, email } => {
+            let access_token = issue_access_token(&email, user_id)?;
+
+            Ok(HttpResponse::Ok().json(ResponseWrapper::<TokenResponse> {
+                message: "Token refreshed successfully".to_string(),
+                data: Some(TokenResponse {
+                    access_token,
+                    refresh_token,
+                }),
+                meta: None,
+            }))
+        }
+        RefreshRotation::Reused => {
+            log::warn!("Rotated refresh token was presented again, revoking its family");
+            Err(AppError::Unauthorized(
+                "Refresh token has already been used, please log in again".to_string(),
+            ))
         }
-        Err(err) => {
-            log::error!("User validation error: {:?}", err);
-            HttpResponse::BadRequest().json(ResponseWrapper::<()> {
-                message: err
-                    .field_errors()
-                    .iter()
-                    .map(|(field, errors)| {
-                        format!(
-                            "Field: {}, Errors: {}",
-                            field,
-                            errors
-                                .iter()
-                                .map(|e| e.message.as_deref().unwrap_or(""))
-                                .collect::<Vec<&str>>()
-                                .join(", ")
-                        )
-                    })
-                    .collect::<Vec<String>>()
-                    .join(", "),
-                data: None,
-            })
+        RefreshRotation::Invalid => {
+            Err(AppError::Unauthorized("Invalid refresh token".to_string()))
         }
     }
 }
 
-#[post("/login")]
-async fn login_user(state: Data<AppState>, user_payload: Json<LoginUser>) -> impl Responder {
-    match user_payload.validate() {
-        Ok(_) => {
-            let user_filter = FilterUser {
-                id: Some(0),
-                name: None,
-                email: Some(user_payload.email.clone()),
-            };
-
-            match find_one_user(&state.db, user_filter).await {
-                Ok(user) => {
-                    match verify_password(&user_payload.password, &user.password) {
-                        Ok(_) => {}
-                        Err(err) => {
-                            return HttpResponse::BadRequest().json(ResponseWrapper::<()> {
-                                message: err.to_string(),
-                                data: None,
-                            });
-                        }
-                    }
-
-                    let token = match get_jwt(user.email.clone(), user.id) {
-                        Ok(token) => token,
-                        Err(err) => {
-                            return HttpResponse::InternalServerError().json(
-                                ResponseWrapper::<()> {
-                                    message: err,
-                                    data: None,
-                                },
-                            );
-                        }
-                    };
-
-                    HttpResponse::Ok().json(ResponseWrapper::<UserResponse> {
-                        message: "User logged in successfully".to_string(),
-                        data: Some(UserResponse {
-                            name: user.name,
-                            email: user.email,
-                            access_token: token,
-                        }),
-                    })
-                }
-                Err(err) => HttpResponse::NotFound().json(ResponseWrapper::<()> {
-                    message: err.to_string(),
-                    data: None,
-                }),
+#[post("/logout")]
+async fn logout_user(
+    state: Data<AppState>,
+    Auth(user): Auth,
+    payload: OptionalJson<LogoutRequest>,
+) -> Result<impl Responder, AppError> {
+    revoke(&state.db, &user.jti, user.exp).await?;
+
+    if let Some(refresh_token) = payload
+        .into_inner()
+        .and_then(|payload| payload.refresh_token)
+    {
+        revoke_refresh_token_family(&state.db, user.id, &hash_refresh_token(&refresh_token))
+            .await?;
+    }
+
+    Ok(HttpResponse::Ok().json(ResponseWrapper::<()> {
+        message: "User logged out successfully".to_string(),
+        data: None,
+        meta: None,
+    }))
+}
+
+#[get("/me")]
+async fn get_profile(state: Data<AppState>, Auth(user): Auth) -> Result<impl Responder, AppError> {
+    let user_filter = FilterUser {
+        id: Some(user.id),
+        name: None,
+        email: None,
+    };
+
+    let user = find_one_user(&state.db, user_filter)
+        .await
+        .or_not_found("User not found")?;
+
+    Ok(HttpResponse::Ok().json(ResponseWrapper::<ProfileResponse> {
+        message: "User profile retrieved successfully".to_string(),
+        data: Some(ProfileResponse {
+            id: user.id,
+            name: user.name,
+            email: user.email,
+            access_token: None,
+            refresh_token: None,
+            updated_at: user.updated_at,
+        }),
+        meta: None,
+    }))
+}
+
+/// Updates the profile. The email is embedded in access tokens, so changing it
+/// revokes the presented token and every refresh token in the same
+/// transaction and returns a fresh pair. Other access tokens already issued
+/// keep the old email until they expire.
+#[patch("/me")]
+async fn update_profile(
+    state: Data<AppState>,
+    Auth(user): Auth,
+    payload: Json<UpdateUserRequest>,
+) -> Result<impl Responder, AppError> {
+    payload.validate()?;
+
+    if payload.is_empty() {
+        return Err(AppError::BadRequest(
+            "At least one field must be provided".to_string(),
+        ));
+    }
+
+    // Issued up front so a signing failure can't leave a half-applied change.
+    let tokens = match payload.email.as_deref() {
+        Some(email) if email != user.email => Some((
+            issue_access_token(email, user.id)?,
+            generate_refresh_token(),
+        )),
+        _ => None,
+    };
+
+    let mut tx = state.db.begin().await?;
+
+    let updated = update_user(&mut *tx, user.id, payload.into_inner())
+        .await
+        .map_err(|err| match err {
+            sqlx::Error::RowNotFound => AppError::NotFound("User not found".to_string()),
+            err => email_conflict(err),
+        })?;
+
+    if let Some((_, refresh_token)) = &tokens {
+        insert_revoked_token(&mut *tx, &user.jti, user.exp).await?;
+        revoke_user_refresh_tokens(&mut *tx, user.id).await?;
+        insert_refresh_token(
+            &mut *tx,
+            user.id,
+            &random_token(16),
+            &hash_refresh_token(refresh_token),
+            refresh_token_days(),
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    if tokens.is_some() {
+        mark_revoked(&user.jti);
+    }
+    let (access_token, refresh_token) = tokens.unzip();
+
+    Ok(HttpResponse::Ok().json(ResponseWrapper::<ProfileResponse> {
+        message: "User profile updated successfully".to_string(),
+        data: Some(ProfileResponse {
+            id: updated.id,
+            name: updated.name,
+            email: updated.email,
+            access_token,
+            refresh_token,
+            updated_at: updated.updated_at,
+        }),
+        meta: None,
+    }))
+}
+
+fn email_conflict(err: sqlx::Error) -> AppError {
+    match sqlstate(&err).as_deref() {
+        Some(UNIQUE_VIOLATION) => AppError::Conflict("Email is already registered".to_string()),
+        _ => err.into(),
+    }
+}
+
+fn issue_access_token(email: &str, user_id: i32) -> Result<String, AppError> {
+    get_jwt(email.to_string(), user_id)
+        .map_err(|err| AppError::Internal(format!("JWT generation error: {}", err)))
+}
+
+/// Starts a new refresh token family for a fresh login.
+async fn issue_refresh_token(state: &AppState, user_id: i32) -> Result<String, AppError> {
+    let refresh_token = generate_refresh_token();
+
+    insert_refresh_token(
+        &state.db,
+        user_id,
+        &random_token(16),
+        &hash_refresh_token(&refresh_token),
+        refresh_token_days(),
+    )
+    .await?;
+
+    Ok(refresh_token)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::test_helpers::{call, call_json, register_user, test_app, test_pool};
+    use actix_web::{http::StatusCode, test::TestRequest};
+    use serde_json::{json, Value};
+
+    fn refresh(token: &Value) -> TestRequest {
+        TestRequest::post()
+            .uri("/v1/user/refresh")
+            .set_json(json!({ "refreshToken": token }))
+    }
+
+    fn profile() -> TestRequest {
+        TestRequest::get().uri("/v1/user/me")
+    }
+
+    fn update_profile(body: Value) -> TestRequest {
+        TestRequest::patch().uri("/v1/user/me").set_json(body)
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    async fn email_is_unique_regardless_of_case() {
+        let app = test_app(test_pool().await).await;
+
+        let email = format!("Case{}@Test.com", chrono::Utc::now().timestamp_micros());
+
+        let cases = [
+            ("/v1/user/register", email.clone(), StatusCode::CREATED),
+            (
+                "/v1/user/register",
+                email.to_lowercase(),
+                StatusCode::CONFLICT,
+            ),
+            ("/v1/user/login", email.to_uppercase(), StatusCode::OK),
+        ];
+        for (uri, email, expected) in cases {
+            let req = TestRequest::post().uri(uri).set_json(json!({
+                "email": email,
+                "name": "Test User",
+                "password": "secret",
+            }));
+            let res = call(&app, req, None).await;
+            assert_eq!(res.status(), expected, "{} {}", uri, email);
+        }
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    async fn reused_refresh_token_revokes_its_family() {
+        let app = test_app(test_pool().await).await;
+
+        let first = register_user(&app).await["refreshToken"].take();
+
+        let (status, body) = call_json(&app, refresh(&first), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let second = &body["data"]["refreshToken"];
+
+        for token in [&first, second] {
+            let res = call(&app, refresh(token), None).await;
+            assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        }
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    async fn logout_revokes_tokens_and_rejects_malformed_bodies() {
+        let app = test_app(test_pool().await).await;
+
+        // The logout body, its content type, the logout status and whether the
+        // access and refresh tokens still work afterwards.
+        let cases = [
+            (None, "application/json", StatusCode::OK, false, true),
+            (
+                Some(r#"{"refreshToken": TOKEN}"#),
+                "application/json",
+                StatusCode::OK,
+                false,
+                false,
+            ),
+            (
+                Some(r#"{"refreshToken": TOKEN}"#),
+                "text/plain",
+                StatusCode::BAD_REQUEST,
+                true,
+                true,
+            ),
+            (
+                Some(r#"{"refreshToken": "#),
+                "application/json",
+                StatusCode::BAD_REQUEST,
+                true,
+                true,
+            ),
+        ];
+        for (body, content_type, expected, access_works, refresh_works) in cases {
+            let user = register_user(&app).await;
+            let token = user["accessToken"].as_str().unwrap();
+
+            let mut logout = TestRequest::post()
+                .uri("/v1/user/logout")
+                .insert_header(("Content-Type", content_type));
+            if let Some(body) = body {
+                logout =
+                    logout.set_payload(body.replace("TOKEN", &user["refreshToken"].to_string()));
             }
+            let res = call(&app, logout, Some(token)).await;
+            assert_eq!(res.status(), expected, "{:?} as {}", body, content_type);
+
+            let res = call(&app, profile(), Some(token)).await;
+            assert_eq!(res.status().is_success(), access_works, "{:?}", body);
+
+            let res = call(&app, refresh(&user["refreshToken"]), None).await;
+            assert_eq!(res.status().is_success(), refresh_works, "{:?}", body);
         }
-        Err(err) => HttpResponse::BadRequest().json(ResponseWrapper::<()> {
-            message: err
-                .field_errors()
-                .iter()
-                .map(|(field, errors)| {
-                    format!(
-                        "Field: {}, Errors: {}",
-                        field,
-                        errors
-                            .iter()
-                            .map(|e| e.message.as_deref().unwrap_or(""))
-                            .collect::<Vec<&str>>()
-                            .join(", ")
-                    )
-                })
-                .collect::<Vec<String>>()
-                .join(", "),
-            data: None,
-        }),
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    async fn email_change_checks_uniqueness_and_reissues_tokens() {
+        let app = test_app(test_pool().await).await;
+
+        let user = register_user(&app).await;
+        let token = user["accessToken"].as_str().unwrap();
+        let taken = register_user(&app).await["email"]
+            .as_str()
+            .unwrap()
+            .to_uppercase();
+        let email = format!("moved{}@test.com", chrono::Utc::now().timestamp_micros());
+
+        // Each update, its status and whether it hands out new tokens.
+        let cases = [
+            (json!({}), StatusCode::BAD_REQUEST, false),
+            (
+                json!({ "email": "not-an-email" }),
+                StatusCode::BAD_REQUEST,
+                false,
+            ),
+            (json!({ "email": taken }), StatusCode::CONFLICT, false),
+            (json!({ "name": "Renamed User" }), StatusCode::OK, false),
+            (json!({ "email": user["email"] }), StatusCode::OK, false),
+            (json!({ "email": email }), StatusCode::OK, true),
+        ];
+        let mut fresh = Value::Null;
+        for (update, expected, reissued) in cases {
+            let (status, mut body) =
+                call_json(&app, update_profile(update.clone()), Some(token)).await;
+            assert_eq!(status, expected, "{}", update);
+            assert_eq!(
+                body["data"]["accessToken"].is_string(),
+                reissued,
+                "{}",
+                update
+            );
+            assert_eq!(
+                body["data"]["refreshToken"].is_string(),
+                reissued,
+                "{}",
+                update
+            );
+
+            if reissued {
+                fresh = body["data"].take();
+            }
+        }
+
+        // The email change ends the old session and starts a new one.
+        let res = call(&app, profile(), Some(token)).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        let res = call(&app, refresh(&user["refreshToken"]), None).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        let res = call(&app, refresh(&fresh["refreshToken"]), None).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let fresh_token = fresh["accessToken"].as_str().unwrap();
+        let (_, body) = call_json(&app, profile(), Some(fresh_token)).await;
+        assert_eq!(body["data"]["email"], email);
+        assert_eq!(body["data"]["name"], "Renamed User");
     }
 }
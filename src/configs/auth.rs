@@ -0,0 +1,69 @@
+use std::{env, sync::OnceLock};
+
+const DEFAULT_ACCESS_TOKEN_MINUTES: i64 = 15;
+const DEFAULT_REFRESH_TOKEN_DAYS: i32 = 30;
+const DEFAULT_JWT_ISSUER: &str = "cats-social";
+const DEFAULT_JWT_AUDIENCE: &str = "cats-social";
+const DEFAULT_JWT_LEEWAY_SECONDS: u64 = 30;
+
+/// Lifetime of an access token, read once from `ACCESS_TOKEN_MINUTES`.
+pub fn access_token_minutes() -> i64 {
+    static ACCESS_TOKEN_MINUTES: OnceLock<i64> = OnceLock::new();
+
+    *ACCESS_TOKEN_MINUTES.get_or_init(|| {
+        env::var("ACCESS_TOKEN_MINUTES")
+            .ok()
+            .and_then(|value| value.parse::<i64>().ok())
+            .filter(|value| *value > 0)
+            .unwrap_or(DEFAULT_ACCESS_TOKEN_MINUTES)
+    })
+}
+
+/// Lifetime of a refresh token, read once from `REFRESH_TOKEN_DAYS`.
+pub fn refresh_token_days() -> i32 {
+    static REFRESH_TOKEN_DAYS: OnceLock<i32> = OnceLock::new();
+
+    *REFRESH_TOKEN_DAYS.get_or_init(|| {
+        env::var("REFRESH_TOKEN_DAYS")
+            .ok()
+            .and_then(|value| value.parse::<i32>().ok())
+            .filter(|value| *value > 0)
+            .unwrap_or(DEFAULT_REFRESH_TOKEN_DAYS)
+    })
+}
+
+/// `iss` claim written into and required on access tokens, from `JWT_ISSUER`.
+pub fn jwt_issuer() -> &'static str {
+    static JWT_ISSUER: OnceLock<String> = OnceLock::new();
+
+    JWT_ISSUER.get_or_init(|| {
+        env::var("JWT_ISSUER")
+            .ok()
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| DEFAULT_JWT_ISSUER.to_string())
+    })
+}
+
+/// `aud` claim written into and required on access tokens, from `JWT_AUDIENCE`.
+pub fn jwt_audience() -> &'static str {
+    static JWT_AUDIENCE: OnceLock<String> = OnceLock::new();
+
+    JWT_AUDIENCE.get_or_init(|| {
+        env::var("JWT_AUDIENCE")
+            .ok()
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| DEFAULT_JWT_AUDIENCE.to_string())
+    })
+}
+
+/// Clock skew tolerated on `exp` and `nbf`, read once from `JWT_LEEWAY_SECONDS`.
+pub fn jwt_leeway_seconds() -> u64 {
+    static JWT_LEEWAY_SECONDS: OnceLock<u64> = OnceLock::new();
+
+    *JWT_LEEWAY_SECONDS.get_or_init(|| {
+        env::var("JWT_LEEWAY_SECONDS")
+            .ok()
+            .and_then(|value| value.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_JWT_LEEWAY_SECONDS)
+    })
+}
@@ -0,0 +1,179 @@
+use jsonwebtoken::{
+    decode, encode,
+    jwk::{JwkSet, KeyAlgorithm},
+    Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use std::{collections::HashMap, env, fmt, fs, sync::OnceLock};
+
+#[derive(Debug)]
+pub struct KeyConfigError(String);
+
+impl fmt::Display for KeyConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid JWT key configuration: {}", self.0)
+    }
+}
+
+impl std::error::Error for KeyConfigError {}
+
+/// Keys used to sign and verify access tokens.
+///
+/// With `JWT_PRIVATE_KEY_PATH`, `JWT_KEY_ID` and `JWT_JWKS_PATH` set, tokens
+/// are signed with the RS256 or EdDSA private key and carry its `kid`; every
+/// public key in the JWK set is accepted, so old keys can stay there until
+/// their tokens expire. Otherwise `JWT_SECRET` signs with HS256.
+pub struct JwtKeys {
+    pub algorithm: Algorithm,
+    pub kid: Option<String>,
+    pub encoding: EncodingKey,
+    pub jwks: JwkSet,
+    verification: HashMap<String, (DecodingKey, Algorithm)>,
+    secret: Option<DecodingKey>,
+}
+
+impl JwtKeys {
+    pub fn from_env() -> Result<Self, KeyConfigError> {
+        let private_key_path = env::var("JWT_PRIVATE_KEY_PATH").ok();
+
+        let Some(private_key_path) = private_key_path else {
+            return env::var("JWT_SECRET")
+                .map(|secret| JwtKeys::from_secret(secret.as_bytes()))
+                .map_err(|_| {
+                    KeyConfigError(
+                        "set JWT_PRIVATE_KEY_PATH, JWT_KEY_ID and JWT_JWKS_PATH, or JWT_SECRET"
+                            .to_string(),
+                    )
+                });
+        };
+
+        let kid = env::var("JWT_KEY_ID")
+            .map_err(|_| KeyConfigError("JWT_KEY_ID must be set".to_string()))?;
+        let jwks_path = env::var("JWT_JWKS_PATH")
+            .map_err(|_| KeyConfigError("JWT_JWKS_PATH must be set".to_string()))?;
+
+        let private_key = read(&private_key_path)?;
+        let jwks = serde_json::from_slice::<JwkSet>(&read(&jwks_path)?)
+            .map_err(|err| KeyConfigError(format!("{}: {}", jwks_path, err)))?;
+
+        JwtKeys::from_pem(&private_key, &kid, jwks)
+    }
+
+    /// Signs with `private_pem` under `kid`, which must be listed in `jwks`.
+    pub fn from_pem(private_pem: &[u8], kid: &str, jwks: JwkSet) -> Result<Self, KeyConfigError> {
+        let mut verification = HashMap::new();
+
+        for jwk in &jwks.keys {
+            let key_id = jwk
+                .common
+                .key_id
+                .clone()
+                .ok_or_else(|| KeyConfigError("every JWK needs a kid".to_string()))?;
+            let algorithm = match jwk.common.key_algorithm {
+                Some(KeyAlgorithm::RS256) => Algorithm::RS256,
+                Some(KeyAlgorithm::EdDSA) => Algorithm::EdDSA,
+                _ => {
+                    return Err(KeyConfigError(format!(
+                        "key {} must have alg RS256 or EdDSA",
+                        key_id
+                    )))
+                }
+            };
+            let key = DecodingKey::from_jwk(jwk)
+                .map_err(|err| KeyConfigError(format!("key {}: {}", key_id, err)))?;
+
+            verification.insert(key_id, (key, algorithm));
+        }
+
+        let (public_key, algorithm) = verification
+            .get(kid)
+            .ok_or_else(|| KeyConfigError(format!("signing key {} is not in the JWKS", kid)))?;
+        let algorithm = *algorithm;
+
+        let encoding = match algorithm {
+            Algorithm::EdDSA => EncodingKey::from_ed_pem(private_pem),
+            _ => EncodingKey::from_rsa_pem(private_pem),
+        }
+        .map_err(|err| KeyConfigError(format!("private key: {}", err)))?;
+
+        check_key_pair(&encoding, public_key, algorithm, kid)?;
+
+        Ok(JwtKeys {
+            algorithm,
+            kid: Some(kid.to_string()),
+            encoding,
+            jwks,
+            verification,
+            secret: None,
+        })
+    }
+
+    /// HS256 keys; nothing is published in the JWKS.
+    pub fn from_secret(secret: &[u8]) -> Self {
+        JwtKeys {
+            algorithm: Algorithm::HS256,
+            kid: None,
+            encoding: EncodingKey::from_secret(secret),
+            jwks: JwkSet { keys: Vec::new() },
+            verification: HashMap::new(),
+            secret: Some(DecodingKey::from_secret(secret)),
+        }
+    }
+
+    /// The key and algorithm a token with header `kid` must be verified with.
+    pub fn verification_key(&self, kid: Option<&str>) -> Option<(&DecodingKey, Algorithm)> {
+        match (&self.secret, kid) {
+            (Some(secret), _) => Some((secret, Algorithm::HS256)),
+            (None, Some(kid)) => self
+                .verification
+                .get(kid)
+                .map(|(key, algorithm)| (key, *algorithm)),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Signs and verifies a probe token so a private key that doesn't match the
+/// published public key fails at startup rather than on every request.
+fn check_key_pair(
+    encoding: &EncodingKey,
+    decoding: &DecodingKey,
+    algorithm: Algorithm,
+    kid: &str,
+) -> Result<(), KeyConfigError> {
+    let probe = encode(&Header::new(algorithm), &serde_json::json!({}), encoding)
+        .map_err(|err| KeyConfigError(format!("private key: {}", err)))?;
+
+    let mut validation = Validation::new(algorithm);
+    validation.required_spec_claims.clear();
+    validation.validate_exp = false;
+
+    decode::<serde_json::Value>(&probe, decoding, &validation)
+        .map(|_| ())
+        .map_err(|_| {
+            KeyConfigError(format!(
+                "private key does not match the public key published as {}",
+                kid
+            ))
+        })
+}
+
+fn read(path: &str) -> Result<Vec<u8>, KeyConfigError> {
+    fs::read(path).map_err(|err| KeyConfigError(format!("{}: {}", path, err)))
+}
+
+static JWT_KEYS: OnceLock<JwtKeys> = OnceLock::new();
+
+/// Loads the keys; called from `main` so a bad configuration stops startup.
+pub fn init_jwt_keys() -> Result<(), KeyConfigError> {
+    if JWT_KEYS.get().is_none() {
+        let _ = JWT_KEYS.set(JwtKeys::from_env()?);
+    }
+
+    Ok(())
+}
+
+/// The keys loaded by [`init_jwt_keys`], or `None` if it hasn't run. Never
+/// loads configuration lazily.
+pub fn jwt_keys() -> Option<&'static JwtKeys> {
+    JWT_KEYS.get()
+}
@@ -1 +1,5 @@
+pub mod auth;
 pub mod db;
+pub mod jwt_keys;
+pub mod pagination;
+pub mod retention;
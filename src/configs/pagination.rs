@@ -0,0 +1,17 @@
+use std::{env, sync::OnceLock};
+
+const DEFAULT_MAX_LIMIT: i32 = 100;
+
+/// Upper bound for the `limit` query parameter on list endpoints, read once
+/// from `MAX_PAGE_LIMIT`.
+pub fn max_limit() -> i32 {
+    static MAX_LIMIT: OnceLock<i32> = OnceLock::new();
+
+    *MAX_LIMIT.get_or_init(|| {
+        env::var("MAX_PAGE_LIMIT")
+            .ok()
+            .and_then(|value| value.parse::<i32>().ok())
+            .filter(|value| *value > 0)
+            .unwrap_or(DEFAULT_MAX_LIMIT)
+    })
+}
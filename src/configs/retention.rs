@@ -0,0 +1,17 @@
+use std::{env, sync::OnceLock};
+
+const DEFAULT_CAT_RESTORE_DAYS: i32 = 30;
+
+/// Number of days a deleted cat can still be restored before it is purged,
+/// read once from `CAT_RESTORE_DAYS`.
+pub fn cat_restore_days() -> i32 {
+    static CAT_RESTORE_DAYS: OnceLock<i32> = OnceLock::new();
+
+    *CAT_RESTORE_DAYS.get_or_init(|| {
+        env::var("CAT_RESTORE_DAYS")
+            .ok()
+            .and_then(|value| value.parse::<i32>().ok())
+            .filter(|value| *value >= 0)
+            .unwrap_or(DEFAULT_CAT_RESTORE_DAYS)
+    })
+}
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::{Validate, ValidationError};
+
+#[derive(Serialize, FromRow)]
+pub struct Breed {
+    pub id: i32,
+    pub slug: String,
+    pub name: String,
+    pub description: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: chrono::NaiveDateTime,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct CreateBreedRequest {
+    #[validate(length(min = 1, max = 50), custom(function = "validate_slug"))]
+    pub slug: String,
+
+    #[validate(length(min = 1, max = 30))]
+    pub name: String,
+
+    #[serde(default)]
+    #[validate(length(max = 200))]
+    pub description: String,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct UpdateBreedRequest {
+    #[validate(length(min = 1, max = 30))]
+    pub name: String,
+
+    #[serde(default)]
+    #[validate(length(max = 200))]
+    pub description: String,
+}
+
+/// Slugs are lowercase ASCII words joined by single hyphens, e.g. `maine-coon`.
+fn validate_slug(slug: &str) -> Result<(), ValidationError> {
+    let valid = slug.split('-').all(|part| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+    });
+
+    if valid {
+        Ok(())
+    } else {
+        Err(ValidationError::new("slug")
+            .with_message("Slug must be lowercase words joined by hyphens".into()))
+    }
+}
@@ -1,3 +1,5 @@
+use crate::entities::{r#match::MatchStatus, SortOrder};
+use crate::helpers::pagination::{Cursor, CursorValue};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use validator::{Validate, ValidationError};
@@ -7,11 +9,9 @@ pub struct CreateCatRequest {
     #[validate(length(min = 1, max = 30))]
     pub name: String,
 
-    #[validate(custom(function = "validate_race"))]
     pub race: String,
 
-    #[validate(custom(function = "validate_sex"))]
-    pub sex: String,
+    pub sex: Sex,
 
     #[serde(rename = "ageInMonth")]
     #[validate(range(min = 1, max = 120082))]
@@ -22,7 +22,39 @@ pub struct CreateCatRequest {
 
     #[serde(rename = "imageUrls")]
     #[validate(custom(function = "validate_image_urls"))]
-    pub img_urls: Vec<String>,
+    pub image_urls: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateCatRequest {
+    #[validate(length(min = 1, max = 30))]
+    pub name: Option<String>,
+
+    pub race: Option<String>,
+
+    pub sex: Option<Sex>,
+
+    #[serde(rename = "ageInMonth")]
+    #[validate(range(min = 1, max = 120082))]
+    pub age_in_month: Option<i32>,
+
+    #[validate(length(min = 1, max = 200))]
+    pub description: Option<String>,
+
+    #[serde(rename = "imageUrls")]
+    #[validate(custom(function = "validate_image_urls"))]
+    pub image_urls: Option<Vec<String>>,
+}
+
+impl UpdateCatRequest {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.race.is_none()
+            && self.sex.is_none()
+            && self.age_in_month.is_none()
+            && self.description.is_none()
+            && self.image_urls.is_none()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
@@ -30,11 +62,9 @@ pub struct CreateCatPayload {
     #[validate(length(min = 1, max = 30))]
     pub name: String,
 
-    #[validate(custom(function = "validate_race"))]
     pub race: String,
 
-    #[validate(custom(function = "validate_sex"))]
-    pub sex: String,
+    pub sex: Sex,
 
     #[serde(rename = "ageInMonth")]
     #[validate(range(min = 1, max = 120082))]
@@ -56,7 +86,7 @@ pub struct Cat {
     pub id: i32,
     pub name: String,
     pub race: String,
-    pub sex: String,
+    pub sex: Sex,
     #[serde(rename = "ageInMonth")]
     pub age_in_month: i32,
     pub description: String,
@@ -64,8 +94,13 @@ pub struct Cat {
     pub img_urls: Vec<String>,
     #[serde(rename = "createdAt")]
     pub created_at: chrono::NaiveDateTime,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: chrono::NaiveDateTime,
     #[serde(rename = "userId")]
     pub user_id: i32,
+    #[serde(rename = "hasMatched")]
+    pub has_matched: bool,
+    pub version: i32,
 }
 
 #[derive(Serialize, FromRow)]
@@ -73,6 +108,10 @@ pub struct CreateCatResponse {
     pub id: i32,
     #[serde(rename = "createdAt")]
     pub created_at: chrono::NaiveDateTime,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: chrono::NaiveDateTime,
+    #[serde(skip)]
+    pub version: i32,
 }
 
 #[derive(Serialize, FromRow)]
@@ -80,7 +119,7 @@ pub struct CatResponse {
     pub id: i32,
     pub name: String,
     pub race: String,
-    pub sex: String,
+    pub sex: Sex,
     #[serde(rename = "ageInMonth")]
     pub age_in_month: i32,
     pub description: String,
@@ -88,10 +127,35 @@ pub struct CatResponse {
     pub img_urls: Vec<String>,
     #[serde(rename = "createdAt")]
     pub created_at: chrono::NaiveDateTime,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: chrono::NaiveDateTime,
     #[serde(rename = "hasMatched")]
     pub has_matched: bool,
 }
 
+#[derive(Serialize)]
+pub struct CatOwner {
+    pub id: i32,
+    pub name: String,
+}
+
+#[derive(Serialize)]
+pub struct CatDetailResponse {
+    #[serde(flatten)]
+    pub cat: CatResponse,
+    pub owner: CatOwner,
+    #[serde(rename = "matchStatus")]
+    pub match_status: Option<MatchStatus>,
+    #[serde(skip)]
+    pub version: i32,
+}
+
+pub struct CatPage {
+    pub cats: Vec<CatResponse>,
+    pub next_cursor: Option<Cursor>,
+    pub total: Option<i64>,
+}
+
 #[derive(Deserialize, Serialize)]
 #[serde(default)]
 pub struct FilterCat {
@@ -99,7 +163,7 @@ pub struct FilterCat {
     pub limit: i32,
     pub offset: i32,
     pub race: Option<String>,
-    pub sex: Option<String>,
+    pub sex: Option<Sex>,
     #[serde(rename = "ageInMonth")]
     pub age_in_month: Option<String>,
     #[serde(rename = "hasMatched")]
@@ -108,6 +172,12 @@ pub struct FilterCat {
     pub search: Option<String>,
     #[serde(rename = "userId")]
     pub user_id: Option<i32>,
+    pub cursor: Option<String>,
+    #[serde(rename = "includeTotal")]
+    pub include_total: Option<bool>,
+    #[serde(rename = "sortBy")]
+    pub sort_by: Option<CatSortBy>,
+    pub order: SortOrder,
 }
 
 impl Default for FilterCat {
@@ -123,41 +193,106 @@ impl Default for FilterCat {
             owned: None,
             search: None,
             user_id: None,
+            cursor: None,
+            include_total: None,
+            sort_by: None,
+            order: SortOrder::Desc,
         }
     }
 }
 
-fn validate_race(race: &str) -> Result<(), ValidationError> {
-    let valid_races = [
-        "Persian",
-        "Maine Coon",
-        "Siamese",
-        "Ragdoll",
-        "Bengal",
-        "Sphynx",
-        "British Shorthair",
-        "Abyssinian",
-        "Scottish Fold",
-        "Birman",
-    ];
-    if valid_races.contains(&race) {
-        Ok(())
-    } else {
-        Err(ValidationError::new("invalid race"))
+impl FilterCat {
+    /// The trimmed search term, if any.
+    pub fn search_term(&self) -> Option<&str> {
+        self.search
+            .as_deref()
+            .map(str::trim)
+            .filter(|search| !search.is_empty())
+    }
+
+    /// Column the listing is keyset-paged by, or `None` when a search without an
+    /// explicit `sortBy` is ranked by relevance instead.
+    pub fn keyset_sort(&self) -> Option<CatSortBy> {
+        match (self.search_term(), self.sort_by) {
+            (Some(_), None) => None,
+            (_, sort_by) => Some(sort_by.unwrap_or_default()),
+        }
+    }
+
+    /// The `sortBy.order` pair stamped into cursors for this listing.
+    pub fn cursor_ordering(&self) -> Option<String> {
+        self.keyset_sort()
+            .map(|sort_by| format!("{}.{}", sort_by.name(), self.order.name()))
     }
 }
 
-fn validate_sex(sex: &str) -> Result<(), ValidationError> {
-    match sex {
-        "male" | "female" => Ok(()),
-        _ => Err(ValidationError::new("invalid sex")),
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "cat_sex", rename_all = "lowercase")]
+pub enum Sex {
+    Male,
+    Female,
+}
+
+/// Columns a cat listing may be sorted by. Only these ever reach the SQL.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum CatSortBy {
+    #[default]
+    #[serde(rename = "createdAt")]
+    CreatedAt,
+    #[serde(rename = "ageInMonth")]
+    AgeInMonth,
+    #[serde(rename = "name")]
+    Name,
+    #[serde(rename = "race")]
+    Race,
+}
+
+impl CatSortBy {
+    /// The `sortBy` value as clients send it.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CatSortBy::CreatedAt => "createdAt",
+            CatSortBy::AgeInMonth => "ageInMonth",
+            CatSortBy::Name => "name",
+            CatSortBy::Race => "race",
+        }
+    }
+
+    pub fn column(&self) -> &'static str {
+        match self {
+            CatSortBy::CreatedAt => "created_at",
+            CatSortBy::AgeInMonth => "age_in_month",
+            CatSortBy::Name => "name",
+            CatSortBy::Race => "race",
+        }
+    }
+
+    pub fn cursor_value(&self, cat: &CatResponse) -> CursorValue {
+        match self {
+            CatSortBy::CreatedAt => CursorValue::Timestamp(cat.created_at),
+            CatSortBy::AgeInMonth => CursorValue::Int(cat.age_in_month),
+            CatSortBy::Name => CursorValue::Text(cat.name.clone()),
+            CatSortBy::Race => CursorValue::Text(cat.race.clone()),
+        }
+    }
+
+    /// Whether `cursor` was issued for a listing sorted by this column.
+    pub fn accepts(&self, cursor: &Cursor) -> bool {
+        matches!(
+            (self, &cursor.value),
+            (CatSortBy::CreatedAt, CursorValue::Timestamp(_))
+                | (CatSortBy::AgeInMonth, CursorValue::Int(_))
+                | (CatSortBy::Name | CatSortBy::Race, CursorValue::Text(_))
+        )
     }
 }
 
 fn validate_image_urls(image_urls: &Vec<String>) -> Result<(), ValidationError> {
     for url in image_urls {
         if url::Url::parse(url).is_err() {
-            return Err(ValidationError::new("invalid url"));
+            return Err(ValidationError::new("url")
+                .with_message("Every image URL must be a valid URL".into()));
         }
     }
     Ok(())
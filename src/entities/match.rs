@@ -0,0 +1,76 @@
+use crate::entities::cat::CatResponse;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "match_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum MatchStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateMatchRequest {
+    #[serde(rename = "matchCatId")]
+    pub match_cat_id: i32,
+
+    #[serde(rename = "userCatId")]
+    pub user_cat_id: i32,
+
+    #[validate(length(min = 5, max = 120))]
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MatchDecisionRequest {
+    #[serde(rename = "matchId")]
+    pub match_id: i32,
+}
+
+#[derive(Debug, FromRow, Deserialize, Serialize)]
+pub struct CatMatch {
+    pub id: i32,
+    #[serde(rename = "userCatId")]
+    pub user_cat_id: Option<i32>,
+    #[serde(rename = "matchCatId")]
+    pub match_cat_id: Option<i32>,
+    pub status: MatchStatus,
+    pub message: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: chrono::NaiveDateTime,
+    #[serde(rename = "deletedAt")]
+    pub deleted_at: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Serialize, FromRow)]
+pub struct CreateMatchResponse {
+    pub id: i32,
+    #[serde(rename = "createdAt")]
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Serialize)]
+pub struct MatchIssuer {
+    pub name: String,
+    pub email: String,
+}
+
+#[derive(Serialize)]
+pub struct MatchResponse {
+    pub id: i32,
+    #[serde(rename = "issuedBy")]
+    pub issued_by: MatchIssuer,
+    #[serde(rename = "matchCatDetail")]
+    pub match_cat_detail: Option<CatResponse>,
+    #[serde(rename = "userCatDetail")]
+    pub user_cat_detail: Option<CatResponse>,
+    pub message: Option<String>,
+    pub status: MatchStatus,
+    #[serde(rename = "createdAt")]
+    pub created_at: chrono::NaiveDateTime,
+    #[serde(rename = "deletedAt")]
+    pub deleted_at: Option<chrono::NaiveDateTime>,
+}
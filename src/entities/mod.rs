@@ -1,10 +1,47 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
+pub mod breed;
 pub mod cat;
+pub mod r#match;
+pub mod refresh_token;
 pub mod user;
 
 #[derive(Serialize)]
 pub struct ResponseWrapper<T> {
     pub message: String,
     pub data: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<PaginationMeta>,
+}
+
+#[derive(Serialize)]
+pub struct PaginationMeta {
+    #[serde(rename = "nextCursor")]
+    pub next_cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        }
+    }
 }
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Deserialize, Validate)]
+pub struct RefreshTokenRequest {
+    #[serde(rename = "refreshToken")]
+    #[validate(length(min = 1))]
+    pub refresh_token: String,
+}
+
+/// Optional logout body; when present, the refresh token's family is revoked too.
+#[derive(Deserialize)]
+pub struct LogoutRequest {
+    #[serde(rename = "refreshToken")]
+    pub refresh_token: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct TokenResponse {
+    #[serde(rename = "accessToken")]
+    pub access_token: String,
+    #[serde(rename = "refreshToken")]
+    pub refresh_token: String,
+}
+
+/// Outcome of presenting a refresh token for rotation.
+pub enum RefreshRotation {
+    Rotated {
+        user_id: i32,
+        email: String,
+    },
+    /// The token had already been rotated; its whole family is now revoked.
+    Reused,
+    /// Unknown, expired or revoked.
+    Invalid,
+}
@@ -38,12 +38,49 @@ pub struct LoginUser {
     pub password: String,
 }
 
+#[derive(Deserialize, Serialize, Validate)]
+pub struct UpdateUserRequest {
+    #[validate(email(message = "Invalid email"))]
+    pub email: Option<String>,
+    #[validate(length(
+        min = 5,
+        max = 50,
+        message = "Name must be between 5 and 50 characters"
+    ))]
+    pub name: Option<String>,
+}
+
+impl UpdateUserRequest {
+    pub fn is_empty(&self) -> bool {
+        self.email.is_none() && self.name.is_none()
+    }
+}
+
+/// The current user's profile. The tokens are only set after an email
+/// change, which ends every other session.
+#[derive(Serialize)]
+pub struct ProfileResponse {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    #[serde(rename = "accessToken", skip_serializing_if = "Option::is_none")]
+    pub access_token: Option<String>,
+    #[serde(rename = "refreshToken", skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: chrono::NaiveDateTime,
+}
+
 #[derive(Serialize)]
 pub struct UserResponse {
     pub name: String,
     pub email: String,
     #[serde(rename = "accessToken")]
     pub access_token: String,
+    #[serde(rename = "refreshToken")]
+    pub refresh_token: String,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: chrono::NaiveDateTime,
 }
 
 #[derive(Debug, FromRow, Deserialize, Serialize)]
@@ -52,6 +89,7 @@ pub struct User {
     pub name: String,
     pub email: String,
     pub password: String,
+    pub updated_at: chrono::NaiveDateTime,
 }
 
 #[derive(Deserialize, Serialize)]
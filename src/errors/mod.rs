@@ -0,0 +1,464 @@
+use crate::entities::ResponseWrapper;
+use actix_web::{error::PathError, http::StatusCode, HttpRequest, HttpResponse, ResponseError};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::{collections::BTreeMap, fmt};
+use validator::ValidationErrors;
+
+pub const UNIQUE_VIOLATION: &str = "23505";
+pub const FOREIGN_KEY_VIOLATION: &str = "23503";
+
+/// Field error code for a JSON body value of the wrong type or variant.
+pub const INVALID_TYPE: &str = "invalid_type";
+/// Field error code for a query parameter that does not parse.
+pub const INVALID_QUERY: &str = "invalid_query";
+
+/// A single problem with one input field.
+#[derive(Debug, Serialize)]
+pub struct FieldError {
+    pub code: String,
+    pub message: String,
+    pub params: Map<String, Value>,
+}
+
+impl FieldError {
+    pub fn new(code: &str, message: impl Into<String>) -> Self {
+        Self {
+            code: code.to_string(),
+            message: message.into(),
+            params: Map::new(),
+        }
+    }
+}
+
+/// Field errors keyed by the field's wire name.
+pub type FieldErrors = BTreeMap<String, Vec<FieldError>>;
+
+#[derive(Serialize)]
+struct ValidationErrorBody<'a> {
+    message: &'a str,
+    errors: &'a FieldErrors,
+}
+
+/// Error returned by handlers and extractors. Every variant renders as a
+/// `ResponseWrapper` body except `Validation`, which lists the offending
+/// fields; `Internal` details are logged and never sent.
+#[derive(Debug)]
+pub enum AppError {
+    Validation(FieldErrors),
+    BadRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
+    NotFound(String),
+    Conflict(String),
+    PreconditionFailed(String),
+    Gone(String),
+    Internal(String),
+}
+
+impl AppError {
+    /// A validation error for a single field.
+    pub fn field(field: &str, error: FieldError) -> Self {
+        AppError::Validation(FieldErrors::from([(field.to_string(), vec![error])]))
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::Validation(_) => write!(f, "Validation failed"),
+            AppError::BadRequest(message)
+            | AppError::Unauthorized(message)
+            | AppError::Forbidden(message)
+            | AppError::NotFound(message)
+            | AppError::Conflict(message)
+            | AppError::PreconditionFailed(message)
+            | AppError::Gone(message)
+            | AppError::Internal(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::Validation(_) | AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
+            AppError::Gone(_) => StatusCode::GONE,
+            AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let message = match self {
+            AppError::Validation(errors) => {
+                return HttpResponse::build(self.status_code()).json(ValidationErrorBody {
+                    message: "Validation failed",
+                    errors,
+                });
+            }
+            AppError::Internal(detail) => {
+                log::error!("Internal error: {}", detail);
+                "Internal server error".to_string()
+            }
+            other => other.to_string(),
+        };
+
+        HttpResponse::build(self.status_code()).json(ResponseWrapper::<()> {
+            message,
+            data: None,
+            meta: None,
+        })
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        if let sqlx::Error::RowNotFound = err {
+            return AppError::NotFound("Resource not found".to_string());
+        }
+
+        match sqlstate(&err).as_deref() {
+            Some(UNIQUE_VIOLATION) => AppError::Conflict("Resource already exists".to_string()),
+            Some(FOREIGN_KEY_VIOLATION) => {
+                AppError::Conflict("Resource is still referenced by other records".to_string())
+            }
+            Some("23502" | "23514" | "22001" | "22P02") => {
+                AppError::BadRequest("Invalid value for a stored field".to_string())
+            }
+            Some("40001" | "40P01") => {
+                AppError::Conflict("Concurrent update detected, please retry".to_string())
+            }
+            _ => AppError::Internal(err.to_string()),
+        }
+    }
+}
+
+/// The SQLSTATE code of a database error, if `err` is one.
+pub fn sqlstate(err: &sqlx::Error) -> Option<String> {
+    err.as_database_error()
+        .and_then(|db_err| db_err.code())
+        .map(|code| code.into_owned())
+}
+
+impl From<ValidationErrors> for AppError {
+    fn from(err: ValidationErrors) -> Self {
+        let errors = err
+            .field_errors()
+            .into_iter()
+            .map(|(field, errors)| {
+                let errors = errors
+                    .iter()
+                    .map(|error| {
+                        // `value` echoes the rejected input, which may be a password.
+                        let params: Map<String, Value> = error
+                            .params
+                            .iter()
+                            .filter(|(name, _)| *name != "value")
+                            .map(|(name, value)| (name.to_string(), value.clone()))
+                            .collect();
+                        let message = error
+                            .message
+                            .as_ref()
+                            .map(|message| message.to_string())
+                            .unwrap_or_else(|| default_message(&error.code, &params));
+
+                        FieldError {
+                            code: error.code.to_string(),
+                            message,
+                            params,
+                        }
+                    })
+                    .collect();
+
+                (camel_case(field), errors)
+            })
+            .collect();
+
+        AppError::Validation(errors)
+    }
+}
+
+/// validator reports Rust field names; the API speaks camelCase.
+fn camel_case(field: &str) -> String {
+    let mut parts = field.split('_');
+    let mut name = parts.next().unwrap_or_default().to_string();
+
+    for part in parts {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            name.extend(first.to_uppercase());
+            name.push_str(chars.as_str());
+        }
+    }
+
+    name
+}
+
+fn default_message(code: &str, params: &Map<String, Value>) -> String {
+    let bounds = match (params.get("min"), params.get("max")) {
+        (Some(min), Some(max)) => format!(" between {} and {}", min, max),
+        (Some(min), None) => format!(" at least {}", min),
+        (None, Some(max)) => format!(" at most {}", max),
+        (None, None) => String::new(),
+    };
+
+    match code {
+        "length" => format!("Length must be{}", bounds),
+        "range" => format!("Value must be{}", bounds),
+        "email" => "Invalid email".to_string(),
+        "url" => "Invalid URL".to_string(),
+        code => format!("Invalid value ({})", code),
+    }
+}
+
+/// Turns a JSON body error at `path` (as reported by `serde_path_to_error`,
+/// `.` for the root) into a field error. Syntax errors are reported against
+/// `body`.
+pub fn json_body_error(path: &str, err: serde_json::Error) -> AppError {
+    let message = strip_position(&err.to_string());
+
+    if !err.is_data() {
+        return AppError::field("body", FieldError::new("invalid_json", message));
+    }
+
+    if let Some(field) = missing_field(&message) {
+        return AppError::field(
+            &nested_field(path, &field),
+            FieldError::new("required", "Field is required"),
+        );
+    }
+
+    let field = match path {
+        "." => "body",
+        path => path,
+    };
+    AppError::field(field, FieldError::new(INVALID_TYPE, message))
+}
+
+fn nested_field(path: &str, field: &str) -> String {
+    match path {
+        "." => field.to_string(),
+        path => format!("{}.{}", path, field),
+    }
+}
+
+/// Turns a query string error at `path` into a field error. serde names the
+/// parameter itself when it is missing.
+pub fn query_error(path: &str, err: serde_urlencoded::de::Error) -> AppError {
+    let message = err.to_string();
+    let field = missing_field(&message).unwrap_or_else(|| match path {
+        "." => "query".to_string(),
+        path => path.to_string(),
+    });
+
+    AppError::field(&field, FieldError::new(INVALID_QUERY, message))
+}
+
+/// Rejects `value` the way serde rejects an unknown enum variant, for fields
+/// whose variants are only known at runtime. `code` is [`INVALID_TYPE`] for a
+/// JSON body and [`INVALID_QUERY`] for a query string.
+pub fn unknown_variant(field: &str, code: &str, value: &str, expected: &[String]) -> AppError {
+    let variants: Vec<String> = expected.iter().map(|name| format!("`{}`", name)).collect();
+    let expected = match variants.as_slice() {
+        [] => "there are no variants".to_string(),
+        [only] => format!("expected {}", only),
+        [first, second] => format!("expected {} or {}", first, second),
+        all => format!("expected one of {}", all.join(", ")),
+    };
+
+    AppError::field(
+        field,
+        FieldError::new(code, format!("unknown variant `{}`, {}", value, expected)),
+    )
+}
+
+/// Reports path errors against the route's parameter when it has just one.
+pub fn path_error_handler(err: PathError, req: &HttpRequest) -> actix_web::Error {
+    let mut names = req.match_info().iter().map(|(name, _)| name);
+    let field = match (names.next(), names.next()) {
+        (Some(name), None) => name.to_string(),
+        _ => "path".to_string(),
+    };
+
+    let message = match &err {
+        PathError::Deserialize(err) => err.to_string(),
+        err => err.to_string(),
+    };
+
+    AppError::field(&field, FieldError::new("invalid_path", message)).into()
+}
+
+fn missing_field(message: &str) -> Option<String> {
+    message
+        .strip_prefix("missing field `")
+        .and_then(|rest| rest.split_once('`'))
+        .map(|(field, _)| field.to_string())
+}
+
+/// Drops serde_json's ` at line X column Y` suffix.
+fn strip_position(message: &str) -> String {
+    match message.rfind(" at line ") {
+        Some(index) => message[..index].to_string(),
+        None => message.to_string(),
+    }
+}
+
+/// Gives a `RowNotFound` a resource-specific message while every other
+/// database error goes through the SQLSTATE mapping.
+pub trait OrNotFound<T> {
+    fn or_not_found(self, message: &str) -> Result<T, AppError>;
+}
+
+impl<T> OrNotFound<T> for Result<T, sqlx::Error> {
+    fn or_not_found(self, message: &str) -> Result<T, AppError> {
+        self.map_err(|err| match err {
+            sqlx::Error::RowNotFound => AppError::NotFound(message.to_string()),
+            err => err.into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use validator::Validate;
+
+    async fn body_json(response: HttpResponse) -> Value {
+        let body = actix_web::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn internal_details_are_not_exposed() {
+        let response = AppError::Internal("connection refused".to_string()).error_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let body = body_json(response).await;
+        assert_eq!(body["message"], "Internal server error");
+    }
+
+    #[test]
+    fn row_not_found_maps_to_not_found() {
+        let err: AppError = sqlx::Error::RowNotFound.into();
+        assert_eq!(err.status_code(), StatusCode::NOT_FOUND);
+
+        let err = Err::<(), _>(sqlx::Error::RowNotFound)
+            .or_not_found("Cat not found")
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Cat not found");
+    }
+
+    #[derive(Validate)]
+    struct Signup {
+        #[validate(length(min = 5, max = 15))]
+        password: String,
+    }
+
+    #[actix_web::test]
+    async fn validation_errors_are_listed_per_field() {
+        let err: AppError = Signup {
+            password: "abc".to_string(),
+        }
+        .validate()
+        .unwrap_err()
+        .into();
+
+        let body = body_json(err.error_response()).await;
+        assert_eq!(
+            body,
+            json!({
+                "message": "Validation failed",
+                "errors": {
+                    "password": [{
+                        "code": "length",
+                        "message": "Length must be between 5 and 15",
+                        "params": { "min": 5, "max": 15 },
+                    }],
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn field_names_use_wire_casing() {
+        assert_eq!(camel_case("age_in_month"), "ageInMonth");
+        assert_eq!(camel_case("email"), "email");
+    }
+
+    #[actix_web::test]
+    async fn missing_json_field_is_reported_by_name() {
+        let err = serde_json::from_str::<Named>("{}").unwrap_err();
+        let err = json_body_error(".", err);
+
+        let body = body_json(err.error_response()).await;
+        assert_eq!(body["errors"]["name"][0]["code"], "required");
+    }
+
+    #[actix_web::test]
+    async fn bad_json_value_is_reported_against_its_field() {
+        let mut deserializer = serde_json::Deserializer::from_str(r#"{"name": 7}"#);
+        let err = serde_path_to_error::deserialize::<_, Named>(&mut deserializer).unwrap_err();
+        let err = json_body_error(&err.path().to_string(), err.into_inner());
+
+        let body = body_json(err.error_response()).await;
+        assert_eq!(body["errors"]["name"][0]["code"], "invalid_type");
+    }
+
+    #[actix_web::test]
+    async fn bad_query_value_is_reported_against_its_parameter() {
+        let deserializer =
+            serde_urlencoded::Deserializer::new(form_urlencoded::parse(b"name=x&limit=ten"));
+        let err = serde_path_to_error::deserialize::<_, Paged>(deserializer).unwrap_err();
+        let err = query_error(&err.path().to_string(), err.into_inner());
+
+        let body = body_json(err.error_response()).await;
+        assert_eq!(body["errors"]["limit"][0]["code"], "invalid_query");
+    }
+
+    #[actix_web::test]
+    async fn runtime_variants_are_rejected_like_enum_variants() {
+        let err = serde_json::from_str::<Painted>(r#"{"color": "pink"}"#).unwrap_err();
+        let from_serde = body_json(json_body_error("color", err).error_response()).await;
+
+        let expected = ["red", "green", "blue"].map(String::from);
+        let err = unknown_variant("color", INVALID_TYPE, "pink", &expected);
+        let from_list = body_json(err.error_response()).await;
+
+        assert_eq!(from_list, from_serde);
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Color {
+        Red,
+        Green,
+        Blue,
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    #[allow(dead_code)]
+    struct Painted {
+        color: Color,
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    #[allow(dead_code)]
+    struct Paged {
+        name: String,
+        limit: i32,
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    #[allow(dead_code)]
+    struct Named {
+        name: String,
+    }
+}
@@ -0,0 +1,67 @@
+use crate::repositories::breed::find_breed_names;
+use sqlx::PgPool;
+use std::{
+    collections::HashSet,
+    sync::{OnceLock, PoisonError, RwLock},
+    time::{Duration, Instant},
+};
+
+/// How long a loaded breed list is trusted. Writes through the admin endpoints
+/// invalidate it immediately; the TTL covers changes made by other instances.
+const CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+struct BreedNames {
+    names: HashSet<String>,
+    loaded_at: Instant,
+}
+
+fn cache() -> &'static RwLock<Option<BreedNames>> {
+    static CACHE: OnceLock<RwLock<Option<BreedNames>>> = OnceLock::new();
+    CACHE.get_or_init(|| RwLock::new(None))
+}
+
+/// Runs `f` on the breed names, only querying the database when the cached
+/// list is missing or stale.
+async fn with_breed_names<R>(
+    pool: &PgPool,
+    f: impl FnOnce(&HashSet<String>) -> R,
+) -> Result<R, sqlx::Error> {
+    if let Some(cached) = cache()
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+    {
+        if cached.loaded_at.elapsed() < CACHE_TTL {
+            return Ok(f(&cached.names));
+        }
+    }
+
+    let names: HashSet<String> = find_breed_names(pool).await?.into_iter().collect();
+    let result = f(&names);
+
+    *cache().write().unwrap_or_else(PoisonError::into_inner) = Some(BreedNames {
+        names,
+        loaded_at: Instant::now(),
+    });
+
+    Ok(result)
+}
+
+/// Whether `name` is the display name of a known breed.
+pub async fn is_known_breed(pool: &PgPool, name: &str) -> Result<bool, sqlx::Error> {
+    with_breed_names(pool, |names| names.contains(name)).await
+}
+
+/// The display names of all known breeds, sorted.
+pub async fn breed_names(pool: &PgPool) -> Result<Vec<String>, sqlx::Error> {
+    with_breed_names(pool, |names| {
+        let mut names: Vec<String> = names.iter().cloned().collect();
+        names.sort();
+        names
+    })
+    .await
+}
+
+pub fn invalidate_breed_cache() {
+    *cache().write().unwrap_or_else(PoisonError::into_inner) = None;
+}
@@ -0,0 +1,155 @@
+use sqlx::{Postgres, QueryBuilder};
+use std::{fmt, str::FromStr};
+
+/// A numeric comparison parsed from a query string such as `>=6`, `<12`, `=3`,
+/// `24` or the inclusive range `6..24`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumericFilter {
+    Eq(i32),
+    Gt(i32),
+    Gte(i32),
+    Lt(i32),
+    Lte(i32),
+    Between(i32, i32),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterParseError {
+    Empty,
+    InvalidNumber(String),
+    InvalidRange { start: i32, end: i32 },
+}
+
+impl fmt::Display for FilterParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterParseError::Empty => write!(f, "filter expression is empty"),
+            FilterParseError::InvalidNumber(value) => {
+                write!(f, "'{}' is not a valid number", value)
+            }
+            FilterParseError::InvalidRange { start, end } => {
+                write!(f, "range start {} is greater than range end {}", start, end)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FilterParseError {}
+
+impl FromStr for NumericFilter {
+    type Err = FilterParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+
+        if input.is_empty() {
+            return Err(FilterParseError::Empty);
+        }
+
+        // Two-character operators must be checked before their one-character prefixes.
+        if let Some(value) = input.strip_prefix(">=") {
+            return parse_number(value).map(NumericFilter::Gte);
+        }
+        if let Some(value) = input.strip_prefix("<=") {
+            return parse_number(value).map(NumericFilter::Lte);
+        }
+        if let Some(value) = input.strip_prefix('>') {
+            return parse_number(value).map(NumericFilter::Gt);
+        }
+        if let Some(value) = input.strip_prefix('<') {
+            return parse_number(value).map(NumericFilter::Lt);
+        }
+        if let Some(value) = input.strip_prefix('=') {
+            return parse_number(value).map(NumericFilter::Eq);
+        }
+        if let Some((start, end)) = input.split_once("..") {
+            let start = parse_number(start)?;
+            let end = parse_number(end)?;
+
+            if start > end {
+                return Err(FilterParseError::InvalidRange { start, end });
+            }
+
+            return Ok(NumericFilter::Between(start, end));
+        }
+
+        parse_number(input).map(NumericFilter::Eq)
+    }
+}
+
+impl NumericFilter {
+    /// Appends `column <op> value` to `query`, binding every value.
+    pub fn push_condition(&self, query: &mut QueryBuilder<Postgres>, column: &str) {
+        query.push(column);
+
+        match *self {
+            NumericFilter::Eq(value) => {
+                query.push(" = ").push_bind(value);
+            }
+            NumericFilter::Gt(value) => {
+                query.push(" > ").push_bind(value);
+            }
+            NumericFilter::Gte(value) => {
+                query.push(" >= ").push_bind(value);
+            }
+            NumericFilter::Lt(value) => {
+                query.push(" < ").push_bind(value);
+            }
+            NumericFilter::Lte(value) => {
+                query.push(" <= ").push_bind(value);
+            }
+            NumericFilter::Between(start, end) => {
+                query
+                    .push(" BETWEEN ")
+                    .push_bind(start)
+                    .push(" AND ")
+                    .push_bind(end);
+            }
+        }
+    }
+}
+
+fn parse_number(value: &str) -> Result<i32, FilterParseError> {
+    let value = value.trim();
+
+    value
+        .parse::<i32>()
+        .map_err(|_| FilterParseError::InvalidNumber(value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_comparison_operators() {
+        assert_eq!("12".parse(), Ok(NumericFilter::Eq(12)));
+        assert_eq!("=12".parse(), Ok(NumericFilter::Eq(12)));
+        assert_eq!(">4".parse(), Ok(NumericFilter::Gt(4)));
+        assert_eq!(">=4".parse(), Ok(NumericFilter::Gte(4)));
+        assert_eq!("<4".parse(), Ok(NumericFilter::Lt(4)));
+        assert_eq!("<=4".parse(), Ok(NumericFilter::Lte(4)));
+    }
+
+    #[test]
+    fn parses_inclusive_ranges() {
+        assert_eq!("6..24".parse(), Ok(NumericFilter::Between(6, 24)));
+        assert_eq!(
+            "24..6".parse::<NumericFilter>(),
+            Err(FilterParseError::InvalidRange { start: 24, end: 6 })
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert_eq!("".parse::<NumericFilter>(), Err(FilterParseError::Empty));
+        assert_eq!(
+            ">abc".parse::<NumericFilter>(),
+            Err(FilterParseError::InvalidNumber("abc".to_string()))
+        );
+        assert_eq!(
+            "6..".parse::<NumericFilter>(),
+            Err(FilterParseError::InvalidNumber("".to_string()))
+        );
+    }
+}
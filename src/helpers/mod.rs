@@ -1,3 +1,8 @@
+pub mod breeds;
+pub mod filters;
 pub mod jwt;
+pub mod pagination;
 pub mod passwords;
+pub mod refresh_tokens;
+pub mod revocations;
 pub mod serde_helpers;
@@ -0,0 +1,120 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, NaiveDateTime};
+use std::fmt;
+
+/// Value of the sort column at the keyset position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CursorValue {
+    Timestamp(NaiveDateTime),
+    Int(i32),
+    Text(String),
+}
+
+/// Keyset position over `(sort column, id)`, handed to clients as an opaque string.
+/// `ordering` names the sort it was issued for (e.g. `name.asc`) so it can't be
+/// replayed against a different one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor {
+    pub ordering: String,
+    pub value: CursorValue,
+    pub id: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidCursor;
+
+impl fmt::Display for InvalidCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cursor is malformed")
+    }
+}
+
+impl std::error::Error for InvalidCursor {}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        let value = match &self.value {
+            CursorValue::Timestamp(value) => format!("t:{}", value.and_utc().timestamp_micros()),
+            CursorValue::Int(value) => format!("i:{}", value),
+            CursorValue::Text(value) => format!("s:{}", value),
+        };
+        URL_SAFE_NO_PAD.encode(format!("{}:{}:{}", self.id, self.ordering, value))
+    }
+
+    pub fn decode(value: &str) -> Result<Self, InvalidCursor> {
+        let raw = URL_SAFE_NO_PAD.decode(value).map_err(|_| InvalidCursor)?;
+        let raw = String::from_utf8(raw).map_err(|_| InvalidCursor)?;
+
+        // The id, ordering and type tag come first so text values may contain ':'.
+        let (id, raw) = raw.split_once(':').ok_or(InvalidCursor)?;
+        let (ordering, raw) = raw.split_once(':').ok_or(InvalidCursor)?;
+        let (tag, value) = raw.split_once(':').ok_or(InvalidCursor)?;
+
+        let id = id.parse::<i32>().map_err(|_| InvalidCursor)?;
+        let value = match tag {
+            "t" => {
+                let micros = value.parse::<i64>().map_err(|_| InvalidCursor)?;
+                let value = DateTime::from_timestamp_micros(micros)
+                    .ok_or(InvalidCursor)?
+                    .naive_utc();
+                CursorValue::Timestamp(value)
+            }
+            "i" => CursorValue::Int(value.parse::<i32>().map_err(|_| InvalidCursor)?),
+            "s" => CursorValue::Text(value.to_string()),
+            _ => return Err(InvalidCursor),
+        };
+
+        Ok(Cursor {
+            ordering: ordering.to_string(),
+            value,
+            id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trips() {
+        let cursors = [
+            Cursor {
+                ordering: "createdAt.desc".to_string(),
+                value: CursorValue::Timestamp(
+                    DateTime::from_timestamp_micros(1_700_000_000_123_456)
+                        .unwrap()
+                        .naive_utc(),
+                ),
+                id: 42,
+            },
+            Cursor {
+                ordering: "ageInMonth.asc".to_string(),
+                value: CursorValue::Int(12),
+                id: 7,
+            },
+            Cursor {
+                ordering: "name.desc".to_string(),
+                value: CursorValue::Text("Mr: Whiskers".to_string()),
+                id: 3,
+            },
+        ];
+
+        for cursor in cursors {
+            assert_eq!(Cursor::decode(&cursor.encode()), Ok(cursor));
+        }
+    }
+
+    #[test]
+    fn rejects_garbage_cursor() {
+        assert_eq!(Cursor::decode("not a cursor"), Err(InvalidCursor));
+        assert_eq!(
+            Cursor::decode(&URL_SAFE_NO_PAD.encode("1:name.asc:i:x")),
+            Err(InvalidCursor)
+        );
+        assert_eq!(
+            Cursor::decode(&URL_SAFE_NO_PAD.encode("1:name.asc:x:2")),
+            Err(InvalidCursor)
+        );
+    }
+}
@@ -18,7 +18,7 @@ pub fn verify_password(
     password_hash: &str,
 ) -> Result<(), argon2::password_hash::Error> {
     let argon2 = Argon2::default();
-    let password_hash = PasswordHash::new(&password_hash)?;
-    let is_valid = argon2.verify_password(password.as_bytes(), &password_hash)?;
-    Ok(is_valid)
+    let password_hash = PasswordHash::new(password_hash)?;
+    argon2.verify_password(password.as_bytes(), &password_hash)?;
+    Ok(())
 }
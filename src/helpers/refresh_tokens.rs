@@ -0,0 +1,34 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use sha2::{Digest, Sha256};
+
+/// A random URL-safe string carrying `bytes` bytes of entropy.
+pub fn random_token(bytes: usize) -> String {
+    let mut buffer = vec![0u8; bytes];
+    OsRng.fill_bytes(&mut buffer);
+    URL_SAFE_NO_PAD.encode(buffer)
+}
+
+pub fn generate_refresh_token() -> String {
+    random_token(32)
+}
+
+/// Refresh tokens are high-entropy, so an unsalted SHA-256 is enough to keep
+/// a database leak from yielding usable tokens while still allowing lookups.
+pub fn hash_refresh_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_unique_and_hash_deterministically() {
+        let token = generate_refresh_token();
+
+        assert_ne!(token, generate_refresh_token());
+        assert_eq!(hash_refresh_token(&token), hash_refresh_token(&token));
+        assert_eq!(hash_refresh_token(&token).len(), 64);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::repositories::revoked_token::{insert_revoked_token, is_token_revoked};
+use sqlx::PgPool;
+use std::{
+    collections::HashMap,
+    sync::{OnceLock, PoisonError, RwLock},
+    time::{Duration, Instant},
+};
+
+/// How long a "not revoked" answer is trusted, bounding how long a logout on
+/// another instance takes to be seen here.
+const NOT_REVOKED_TTL: Duration = Duration::from_secs(5);
+
+/// Revocations are permanent, so positive answers can be kept much longer.
+const REVOKED_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Past this many entries, stale ones are dropped on the next insert.
+const MAX_ENTRIES: usize = 10_000;
+
+struct Lookup {
+    revoked: bool,
+    checked_at: Instant,
+}
+
+impl Lookup {
+    fn is_fresh(&self) -> bool {
+        let ttl = if self.revoked {
+            REVOKED_TTL
+        } else {
+            NOT_REVOKED_TTL
+        };
+        self.checked_at.elapsed() < ttl
+    }
+}
+
+fn cache() -> &'static RwLock<HashMap<String, Lookup>> {
+    static CACHE: OnceLock<RwLock<HashMap<String, Lookup>>> = OnceLock::new();
+    CACHE.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+fn remember(jti: &str, revoked: bool) {
+    let mut cache = cache().write().unwrap_or_else(PoisonError::into_inner);
+
+    if cache.len() >= MAX_ENTRIES {
+        cache.retain(|_, lookup| lookup.is_fresh());
+    }
+
+    cache.insert(
+        jti.to_string(),
+        Lookup {
+            revoked,
+            checked_at: Instant::now(),
+        },
+    );
+}
+
+/// Whether the token `jti` has been revoked. Looks up the single jti and
+/// caches the answer briefly.
+pub async fn is_revoked(pool: &PgPool, jti: &str) -> Result<bool, sqlx::Error> {
+    if let Some(lookup) = cache()
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(jti)
+        .filter(|lookup| lookup.is_fresh())
+    {
+        return Ok(lookup.revoked);
+    }
+
+    let revoked = is_token_revoked(pool, jti).await?;
+    remember(jti, revoked);
+
+    Ok(revoked)
+}
+
+/// Revokes `jti` until `exp`, taking effect on this instance immediately.
+pub async fn revoke(pool: &PgPool, jti: &str, exp: i64) -> Result<(), sqlx::Error> {
+    insert_revoked_token(pool, jti, exp).await?;
+    mark_revoked(jti);
+
+    Ok(())
+}
+
+/// Records a revocation the caller has already committed, e.g. as part of a
+/// larger transaction.
+pub fn mark_revoked(jti: &str) {
+    remember(jti, true);
+}
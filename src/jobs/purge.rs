@@ -0,0 +1,31 @@
+use crate::{
+    configs::retention::cat_restore_days,
+    repositories::{cat::purge_deleted_cats, revoked_token::purge_revoked_tokens},
+};
+use sqlx::PgPool;
+use std::time::Duration;
+
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Spawns a background task that, once at startup and then every hour,
+/// hard-deletes cats whose restore window has passed and drops revocations of
+/// access tokens that have expired anyway.
+pub fn spawn_purge(pool: PgPool) {
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(PURGE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            match purge_deleted_cats(&pool, cat_restore_days()).await {
+                Ok(0) => {}
+                Ok(purged) => log::info!("Purged {} deleted cats", purged),
+                Err(err) => log::error!("Failed to purge deleted cats: {}", err),
+            }
+
+            if let Err(err) = purge_revoked_tokens(&pool).await {
+                log::error!("Failed to purge expired token revocations: {}", err);
+            }
+        }
+    });
+}
@@ -1,13 +1,17 @@
 mod api;
 mod configs;
 mod entities;
+mod errors;
 mod helpers;
+mod jobs;
 mod middlewares;
 mod repositories;
 
 use api::run_server;
 use configs::db::create_pool;
+use configs::jwt_keys::init_jwt_keys;
 use dotenv::dotenv;
+use jobs::purge::spawn_purge;
 use sqlx::{Pool, Postgres};
 
 pub struct AppState {
@@ -29,6 +33,10 @@ async fn main() -> std::io::Result<()> {
     }
     env_logger::init();
 
+    init_jwt_keys().map_err(std::io::Error::other)?;
+
     let pool = create_pool().await;
+    spawn_purge(pool.clone());
+
     run_server(pool).await
 }
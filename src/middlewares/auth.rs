@@ -1,14 +1,19 @@
-use crate::entities::ResponseWrapper;
+use crate::errors::AppError;
 use crate::helpers::jwt::{decode_jwt, TokenUser};
-use actix_web::{error::InternalError, http::header, FromRequest, HttpResponse};
-use std::future::{ready, Ready};
+use crate::helpers::revocations::is_revoked;
+use crate::repositories::user::is_admin;
+use crate::AppState;
+use actix_web::{http::header, web::Data, FromRequest};
+use std::future::Future;
+use std::pin::Pin;
 
+/// Authenticates the bearer token and rejects tokens revoked through logout.
 pub struct Auth(pub TokenUser);
 
 impl FromRequest for Auth {
-    type Error = InternalError<String>;
+    type Error = AppError;
 
-    type Future = Ready<Result<Self, Self::Error>>;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
 
     fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
         let access_token = req
@@ -17,28 +22,54 @@ impl FromRequest for Auth {
             .and_then(|value| value.to_str().ok())
             .and_then(|str| str.split(" ").nth(1));
 
-        match access_token {
-            Some(token) => {
-                let user = decode_jwt(token);
-
-                match user {
-                    Ok(user) => ready(Ok(Auth(user))),
-                    Err(err) => ready(Err(InternalError::from_response(
-                        err.clone(),
-                        HttpResponse::Unauthorized().json(ResponseWrapper::<()> {
-                            message: err,
-                            data: None,
-                        }),
-                    ))),
-                }
+        let user = match access_token {
+            Some(token) => decode_jwt(token).map_err(AppError::Unauthorized),
+            None => Err(AppError::Unauthorized("Unauthorized".to_string())),
+        };
+        let state = req.app_data::<Data<AppState>>().cloned();
+
+        Box::pin(async move {
+            let user = user?;
+            let state = state.ok_or_else(missing_state)?;
+
+            if is_revoked(&state.db, &user.jti).await? {
+                return Err(AppError::Unauthorized("Token has been revoked".to_string()));
+            }
+
+            Ok(Auth(user))
+        })
+    }
+}
+
+/// Like [`Auth`], but also requires the user to be flagged as an admin. The
+/// flag is read from the database on every request so revoking it is immediate.
+pub struct Admin(pub TokenUser);
+
+impl FromRequest for Admin {
+    type Error = AppError;
+
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(
+        req: &actix_web::HttpRequest,
+        payload: &mut actix_web::dev::Payload,
+    ) -> Self::Future {
+        let auth = Auth::from_request(req, payload);
+        let state = req.app_data::<Data<AppState>>().cloned();
+
+        Box::pin(async move {
+            let Auth(user) = auth.await?;
+            let state = state.ok_or_else(missing_state)?;
+
+            if is_admin(&state.db, user.id).await? {
+                Ok(Admin(user))
+            } else {
+                Err(AppError::Forbidden("Admin access required".to_string()))
             }
-            None => ready(Err(InternalError::from_response(
-                "Unauthorized".to_string(),
-                HttpResponse::Unauthorized().json(ResponseWrapper::<()> {
-                    message: "Unauthorized".to_string(),
-                    data: None,
-                }),
-            ))),
-        }
+        })
     }
 }
+
+fn missing_state() -> AppError {
+    AppError::Internal("AppState is not registered".to_string())
+}
@@ -0,0 +1,165 @@
+use crate::errors::{json_body_error, query_error, AppError, FieldError};
+use actix_web::{error::PayloadError, web::Bytes, FromRequest, HttpMessage};
+use serde::de::DeserializeOwned;
+use std::future::{ready, Future, Ready};
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+
+/// Largest JSON body accepted, the same as actix's `JsonConfig` default so
+/// bodies that fit before still do.
+pub const JSON_LIMIT: usize = 2 * 1024 * 1024;
+
+/// JSON body extractor that reports deserialization errors against the
+/// offending field, e.g. an unknown `sex` variant under `sex` rather than
+/// under `body` as actix's `web::Json` would. The body is read as `Bytes`, so
+/// its size is capped by the `PayloadConfig` that `base_path` sets to
+/// [`JSON_LIMIT`].
+pub struct Json<T>(pub T);
+
+impl<T> Json<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Json<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Json<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: DeserializeOwned + 'static> FromRequest for Json<T> {
+    type Error = AppError;
+
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(
+        req: &actix_web::HttpRequest,
+        payload: &mut actix_web::dev::Payload,
+    ) -> Self::Future {
+        let is_json = is_json(req);
+        let body = Bytes::from_request(req, payload);
+
+        Box::pin(async move {
+            if !is_json {
+                return Err(content_type_error());
+            }
+
+            let body = body.await.map_err(read_error)?;
+            parse_json(&body).map(Json)
+        })
+    }
+}
+
+/// Like [`Json`], but an empty body yields `None`. A body that is present is
+/// held to the same rules, unlike `Option<Json<T>>`, which would turn a wrong
+/// content type or malformed JSON into `None` too.
+pub struct OptionalJson<T>(pub Option<T>);
+
+impl<T> OptionalJson<T> {
+    pub fn into_inner(self) -> Option<T> {
+        self.0
+    }
+}
+
+impl<T: DeserializeOwned + 'static> FromRequest for OptionalJson<T> {
+    type Error = AppError;
+
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(
+        req: &actix_web::HttpRequest,
+        payload: &mut actix_web::dev::Payload,
+    ) -> Self::Future {
+        let is_json = is_json(req);
+        let body = Bytes::from_request(req, payload);
+
+        Box::pin(async move {
+            let body = body.await.map_err(read_error)?;
+
+            if body.is_empty() {
+                return Ok(OptionalJson(None));
+            }
+
+            if !is_json {
+                return Err(content_type_error());
+            }
+
+            parse_json(&body).map(|value| OptionalJson(Some(value)))
+        })
+    }
+}
+
+fn is_json(req: &actix_web::HttpRequest) -> bool {
+    let content_type = req.content_type();
+    content_type == "application/json" || content_type.ends_with("+json")
+}
+
+fn content_type_error() -> AppError {
+    AppError::field(
+        "body",
+        FieldError::new("content_type", "Content type must be application/json"),
+    )
+}
+
+fn read_error(err: actix_web::Error) -> AppError {
+    match err.as_error::<PayloadError>() {
+        Some(PayloadError::Overflow) => AppError::field(
+            "body",
+            FieldError::new(
+                "too_large",
+                format!("Body must not exceed {} bytes", JSON_LIMIT),
+            ),
+        ),
+        _ => AppError::field("body", FieldError::new("invalid_body", err.to_string())),
+    }
+}
+
+fn parse_json<T: DeserializeOwned>(body: &[u8]) -> Result<T, AppError> {
+    let mut deserializer = serde_json::Deserializer::from_slice(body);
+    let value = serde_path_to_error::deserialize(&mut deserializer)
+        .map_err(|err| json_body_error(&err.path().to_string(), err.into_inner()))?;
+    deserializer
+        .end()
+        .map_err(|err| json_body_error(".", err))?;
+
+    Ok(value)
+}
+
+/// Query string extractor that, like [`Json`], reports a bad value against
+/// the parameter it came from.
+pub struct Query<T>(pub T);
+
+impl<T> Deref for Query<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: DeserializeOwned> FromRequest for Query<T> {
+    type Error = AppError;
+
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        let deserializer = serde_urlencoded::Deserializer::new(form_urlencoded::parse(
+            req.query_string().as_bytes(),
+        ));
+
+        ready(
+            serde_path_to_error::deserialize(deserializer)
+                .map(Query)
+                .map_err(|err| query_error(&err.path().to_string(), err.into_inner())),
+        )
+    }
+}
@@ -0,0 +1,58 @@
+use crate::entities::breed::{Breed, CreateBreedRequest, UpdateBreedRequest};
+use sqlx::PgPool;
+
+pub async fn find_many_breeds(pool: &PgPool) -> Result<Vec<Breed>, sqlx::Error> {
+    sqlx::query_as::<_, Breed>(
+        "SELECT id, slug, name, description, created_at, updated_at FROM breeds ORDER BY name ASC",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn find_breed_names(pool: &PgPool) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, String>("SELECT name FROM breeds")
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn insert_breed(pool: &PgPool, breed: CreateBreedRequest) -> Result<Breed, sqlx::Error> {
+    sqlx::query_as::<_, Breed>(
+        "INSERT INTO breeds (slug, name, description) VALUES ($1, $2, $3) RETURNING id, slug, name, description, created_at, updated_at",
+    )
+    .bind(breed.slug)
+    .bind(breed.name)
+    .bind(breed.description)
+    .fetch_one(pool)
+    .await
+}
+
+/// Updates a breed by slug. Renaming it renames the race of its cats as well.
+pub async fn update_breed(
+    pool: &PgPool,
+    slug: &str,
+    breed: UpdateBreedRequest,
+) -> Result<Breed, sqlx::Error> {
+    sqlx::query_as::<_, Breed>(
+        "UPDATE breeds SET name = $2, description = $3 WHERE slug = $1 RETURNING id, slug, name, description, created_at, updated_at",
+    )
+    .bind(slug)
+    .bind(breed.name)
+    .bind(breed.description)
+    .fetch_one(pool)
+    .await
+}
+
+/// Deletes a breed by slug. Fails with a foreign key violation while any cat,
+/// including a soft-deleted one, still uses it.
+pub async fn delete_breed(pool: &PgPool, slug: &str) -> Result<(), sqlx::Error> {
+    let result = sqlx::query("DELETE FROM breeds WHERE slug = $1")
+        .bind(slug)
+        .execute(pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    Ok(())
+}
@@ -1,4 +1,16 @@
-use crate::entities::cat::{Cat, CatResponse, CreateCatPayload, CreateCatResponse, FilterCat};
+use crate::{
+    entities::{
+        cat::{
+            Cat, CatDetailResponse, CatOwner, CatPage, CatResponse, CreateCatPayload,
+            CreateCatResponse, FilterCat, UpdateCatRequest,
+        },
+        SortOrder,
+    },
+    helpers::{
+        filters::NumericFilter,
+        pagination::{Cursor, CursorValue},
+    },
+};
 use sqlx::{PgPool, QueryBuilder, Row};
 
 pub async fn insert_cat(
@@ -6,11 +18,11 @@ pub async fn insert_cat(
     cat: CreateCatPayload,
 ) -> Result<CreateCatResponse, sqlx::Error> {
     sqlx::query_as::<_, CreateCatResponse>(
-        "INSERT INTO cats (name, race, sex, age_in_month, description, img_urls, user_id) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id, created_at",
+        "INSERT INTO cats (name, race, sex, age_in_month, description, img_urls, user_id) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id, created_at, updated_at, version",
     )
     .bind(cat.name.to_string())
-    .bind(cat.race.to_string())
-    .bind(cat.sex.to_string())
+    .bind(cat.race)
+    .bind(cat.sex)
     .bind(cat.age_in_month)
     .bind(cat.description.to_string())
     .bind(cat.img_urls)
@@ -19,134 +31,405 @@ pub async fn insert_cat(
     .await
 }
 
+/// Lists cats matching `filter`, ordered by `sortBy`/`order` with `id` as a tie
+/// breaker and paged with `cursor` or `offset`. A search without `sortBy` is
+/// ranked by relevance and paged with `offset` only; callers reject a `cursor`
+/// there, and no `next_cursor` is returned.
 pub async fn find_many_cats(
     pool: &PgPool,
     filter: FilterCat,
-) -> Result<Vec<CatResponse>, sqlx::Error> {
-    let mut query = QueryBuilder::<sqlx::Postgres>::new("SELECT id, name, race, sex, age_in_month, description, img_urls, created_at, user_id FROM cats WHERE 1=1 ");
-    let mut has_condition = true;
+    age_in_month: Option<NumericFilter>,
+    cursor: Option<Cursor>,
+) -> Result<CatPage, sqlx::Error> {
+    let search = filter.search_term();
+    let sort_by = filter.keyset_sort();
+    let order = filter.order.as_sql();
 
+    let mut query = QueryBuilder::<sqlx::Postgres>::new("SELECT id, name, race, sex, age_in_month, description, img_urls, created_at, updated_at, user_id, has_matched FROM cats WHERE deleted_at IS NULL");
+    push_filters(&mut query, &filter, age_in_month, search);
+
+    if let (Some(sort_by), Some(cursor)) = (sort_by, &cursor) {
+        let comparison = match filter.order {
+            SortOrder::Asc => ">",
+            SortOrder::Desc => "<",
+        };
+        query.push(format!(" AND ({}, id) {} (", sort_by.column(), comparison));
+        match &cursor.value {
+            CursorValue::Timestamp(value) => query.push_bind(*value),
+            CursorValue::Int(value) => query.push_bind(*value),
+            CursorValue::Text(value) => query.push_bind(value.clone()),
+        };
+        query.push(", ");
+        query.push_bind(cursor.id);
+        query.push(")");
+    }
+
+    match sort_by {
+        Some(sort_by) => {
+            query.push(format!(
+                " ORDER BY {} {}, id {}",
+                sort_by.column(),
+                order,
+                order
+            ));
+        }
+        None => {
+            let search = search.unwrap_or_default();
+            query.push(" ORDER BY GREATEST(word_similarity(");
+            query.push_bind(search.to_string());
+            query.push(", name), word_similarity(");
+            query.push_bind(search.to_string());
+            query.push(", description)) DESC, created_at DESC, id DESC");
+        }
+    }
+
+    // Fetch one extra row to learn whether another page exists.
+    query.push(" LIMIT ");
+    query.push_bind(filter.limit + 1);
+    if sort_by.is_none() || cursor.is_none() {
+        query.push(" OFFSET ");
+        query.push_bind(filter.offset);
+    }
+
+    let mut cats: Vec<CatResponse> = query
+        .build()
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|row| CatResponse {
+            id: row.get("id"),
+            name: row.get("name"),
+            race: row.get("race"),
+            sex: row.get("sex"),
+            age_in_month: row.get("age_in_month"),
+            description: row.get("description"),
+            img_urls: row.get("img_urls"),
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+            has_matched: row.get("has_matched"),
+        })
+        .collect();
+
+    let has_more = cats.len() > filter.limit as usize;
+    cats.truncate(filter.limit as usize);
+
+    let next_cursor = match (sort_by, has_more, cats.last()) {
+        (Some(sort_by), true, Some(last)) => Some(Cursor {
+            ordering: filter.cursor_ordering().unwrap_or_default(),
+            value: sort_by.cursor_value(last),
+            id: last.id,
+        }),
+        _ => None,
+    };
+
+    let total = if filter.include_total == Some(true) {
+        let mut query = QueryBuilder::<sqlx::Postgres>::new(
+            "SELECT COUNT(*) FROM cats WHERE deleted_at IS NULL",
+        );
+        push_filters(&mut query, &filter, age_in_month, search);
+        Some(query.build_query_scalar::<i64>().fetch_one(pool).await?)
+    } else {
+        None
+    };
+
+    Ok(CatPage {
+        cats,
+        next_cursor,
+        total,
+    })
+}
+
+fn push_filters(
+    query: &mut QueryBuilder<sqlx::Postgres>,
+    filter: &FilterCat,
+    age_in_month: Option<NumericFilter>,
+    search: Option<&str>,
+) {
     if let Some(id) = filter.id {
         if id > 0 {
-            if has_condition {
-                query.push(" AND ");
-            }
-            query.push("id = ");
+            query.push(" AND id = ");
             query.push_bind(id);
-            has_condition = true;
         }
     }
 
-    if let Some(search) = filter.search {
-        if has_condition {
-            query.push(" AND ");
-        }
-        query.push("name = ");
-        query.push_bind(search);
-        has_condition = true;
+    if let Some(search) = search {
+        let pattern = format!("%{}%", escape_like(search));
+        query.push(" AND (name ILIKE ");
+        query.push_bind(pattern.clone());
+        query.push(" OR description ILIKE ");
+        query.push_bind(pattern);
+        query.push(" OR name % ");
+        query.push_bind(search.to_string());
+        query.push(")");
     }
 
-    if let Some(race) = filter.race {
-        if has_condition {
-            query.push(" AND ");
-        }
-        query.push("race = ");
-        query.push_bind(race);
-        has_condition = true;
+    if let Some(race) = &filter.race {
+        query.push(" AND race = ");
+        query.push_bind(race.clone());
     }
 
     if let Some(sex) = filter.sex {
-        if has_condition {
-            query.push(" AND ");
-        }
-        query.push("sex = ");
+        query.push(" AND sex = ");
         query.push_bind(sex);
-        has_condition = true;
     }
 
-    if let Some(age_in_month) = filter.age_in_month {
-        if has_condition {
-            query.push(" AND ");
-        }
-        if age_in_month.starts_with('>') {
-            query.push("age_in_month > ");
-            query.push_bind(age_in_month[1..].parse::<i32>().unwrap());
-        } else if age_in_month.starts_with('<') {
-            query.push("age_in_month < ");
-            query.push_bind(age_in_month[1..].parse::<i32>().unwrap());
-        } else {
-            query.push("age_in_month = ");
-            query.push_bind(age_in_month[1..].parse::<i32>().unwrap());
-        }
-        has_condition = true;
+    if let Some(age_in_month) = age_in_month {
+        query.push(" AND ");
+        age_in_month.push_condition(query, "age_in_month");
     }
 
-    if let Some(_) = filter.owned {
-        if has_condition {
-            query.push(" AND ");
-        }
-        query.push("user_id = ");
-        query.push_bind(filter.user_id);
+    if let Some(has_matched) = filter.has_matched {
+        query.push(" AND has_matched = ");
+        query.push_bind(has_matched);
     }
 
-    query.push(" ORDER BY created_at DESC");
+    if filter.owned.is_some() {
+        query.push(" AND user_id = ");
+        query.push_bind(filter.user_id);
+    }
+}
 
-    query.push(" LIMIT ");
-    query.push_bind(filter.limit);
-    query.push(" OFFSET ");
-    query.push_bind(filter.offset);
-
-    let query = query.build();
-
-    query.fetch_all(pool).await.map(|rows| {
-        rows.iter()
-            .map(|row| CatResponse {
-                id: row.get("id"),
-                name: row.get("name"),
-                race: row.get("race"),
-                sex: row.get("sex"),
-                age_in_month: row.get("age_in_month"),
-                description: row.get("description"),
-                img_urls: row.get("img_urls"),
-                created_at: row.get("created_at"),
-                has_matched: false,
-            })
-            .collect()
+/// Loads a cat with its owner's public profile and the status of its most
+/// relevant live match request (approved before pending).
+pub async fn find_cat_detail(pool: &PgPool, id: i32) -> Result<CatDetailResponse, sqlx::Error> {
+    sqlx::query(
+        "SELECT c.id, c.name, c.race, c.sex, c.age_in_month, c.description, c.img_urls, \
+            c.created_at, c.updated_at, c.has_matched, c.version, u.id AS owner_id, u.name AS owner_name, \
+            (SELECT m.status FROM cat_matches m \
+                WHERE (m.user_cat_id = c.id OR m.match_cat_id = c.id) \
+                AND m.deleted_at IS NULL AND m.status IN ('approved', 'pending') \
+                ORDER BY m.status = 'approved' DESC LIMIT 1) AS match_status \
+        FROM cats c \
+        JOIN users u ON u.id = c.user_id \
+        WHERE c.id = $1 AND c.deleted_at IS NULL",
+    )
+    .bind(id)
+    .fetch_one(pool)
+    .await
+    .map(|row| CatDetailResponse {
+        cat: CatResponse {
+            id: row.get("id"),
+            name: row.get("name"),
+            race: row.get("race"),
+            sex: row.get("sex"),
+            age_in_month: row.get("age_in_month"),
+            description: row.get("description"),
+            img_urls: row.get("img_urls"),
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+            has_matched: row.get("has_matched"),
+        },
+        owner: CatOwner {
+            id: row.get("owner_id"),
+            name: row.get("owner_name"),
+        },
+        match_status: row.get("match_status"),
+        version: row.get("version"),
     })
 }
 
 pub async fn find_one_cat(pool: &PgPool, id: i32) -> Result<Cat, sqlx::Error> {
     sqlx::query_as::<_, Cat>(
-        "SELECT id, name, race, sex, age_in_month, description, img_urls, created_at, user_id FROM cats WHERE id = $1",
+        "SELECT id, name, race, sex, age_in_month, description, img_urls, created_at, updated_at, user_id, has_matched, version FROM cats WHERE id = $1 AND deleted_at IS NULL",
     )
     .bind(id)
     .fetch_one(pool)
     .await
 }
 
+/// Replaces a cat owned by `cat.user_id`. When `expected_version` is set the
+/// update only applies if the row is still at that version, otherwise
+/// `RowNotFound` is returned, as it is for a cat of another owner.
 pub async fn update_cat(
     pool: &PgPool,
     id: i32,
     cat: CreateCatPayload,
+    expected_version: Option<i32>,
 ) -> Result<CreateCatResponse, sqlx::Error> {
     sqlx::query_as::<_, CreateCatResponse>(
-        "UPDATE cats SET name = $2, race = $3, sex = $4, age_in_month = $5, description = $6, img_urls = $7 WHERE id = $1 RETURNING id, created_at",
+        "UPDATE cats SET name = $2, race = $3, sex = $4, age_in_month = $5, description = $6, img_urls = $7 WHERE id = $1 AND user_id = $9 AND deleted_at IS NULL AND ($8::INT IS NULL OR version = $8) RETURNING id, created_at, updated_at, version",
     )
     .bind(id)
     .bind(cat.name.to_string())
-    .bind(cat.race.to_string())
-    .bind(cat.sex.to_string())
+    .bind(cat.race)
+    .bind(cat.sex)
     .bind(cat.age_in_month)
     .bind(cat.description.to_string())
     .bind(cat.img_urls)
+    .bind(expected_version)
+    .bind(cat.user_id)
     .fetch_one(pool)
     .await
 }
 
-pub async fn delete_cat(pool: &PgPool, id: i32) -> Result<(), sqlx::Error> {
-    sqlx::query("DELETE FROM cats WHERE id = $1")
-        .bind(id)
-        .execute(pool)
+/// Updates only the fields present in `cat`, which must not be empty, with
+/// the same `expected_version` and ownership semantics as [`update_cat`].
+pub async fn partial_update_cat(
+    pool: &PgPool,
+    id: i32,
+    user_id: i32,
+    cat: UpdateCatRequest,
+    expected_version: Option<i32>,
+) -> Result<CreateCatResponse, sqlx::Error> {
+    let mut query = QueryBuilder::<sqlx::Postgres>::new("UPDATE cats SET ");
+    let mut fields = query.separated(", ");
+
+    if let Some(name) = cat.name {
+        fields.push("name = ");
+        fields.push_bind_unseparated(name);
+    }
+
+    if let Some(race) = cat.race {
+        fields.push("race = ");
+        fields.push_bind_unseparated(race);
+    }
+
+    if let Some(sex) = cat.sex {
+        fields.push("sex = ");
+        fields.push_bind_unseparated(sex);
+    }
+
+    if let Some(age_in_month) = cat.age_in_month {
+        fields.push("age_in_month = ");
+        fields.push_bind_unseparated(age_in_month);
+    }
+
+    if let Some(description) = cat.description {
+        fields.push("description = ");
+        fields.push_bind_unseparated(description);
+    }
+
+    if let Some(img_urls) = cat.image_urls {
+        fields.push("img_urls = ");
+        fields.push_bind_unseparated(img_urls);
+    }
+
+    query.push(" WHERE id = ");
+    query.push_bind(id);
+    query.push(" AND user_id = ");
+    query.push_bind(user_id);
+    query.push(" AND deleted_at IS NULL");
+
+    if let Some(expected_version) = expected_version {
+        query.push(" AND version = ");
+        query.push_bind(expected_version);
+    }
+    query.push(" RETURNING id, created_at, updated_at, version");
+
+    query
+        .build_query_as::<CreateCatResponse>()
+        .fetch_one(pool)
         .await
-        .map(|_| ())
+}
+
+/// Soft-deletes a cat owned by `user_id` together with its live match
+/// requests, or returns `RowNotFound`. The partner of an approved match
+/// becomes available again. The cat can be restored until
+/// [`purge_deleted_cats`] removes it for good.
+pub async fn delete_cat(pool: &PgPool, id: i32, user_id: i32) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query_scalar::<_, i32>(
+        "UPDATE cats SET deleted_at = CURRENT_TIMESTAMP WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL RETURNING id",
+    )
+    .bind(id)
+    .bind(user_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "UPDATE cats SET has_matched = FALSE WHERE id IN (SELECT CASE WHEN user_cat_id = $1 THEN match_cat_id ELSE user_cat_id END FROM cat_matches WHERE (user_cat_id = $1 OR match_cat_id = $1) AND status = 'approved' AND deleted_at IS NULL)",
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
+
+    // CURRENT_TIMESTAMP is fixed for the transaction, so the matches share the
+    // cat's deleted_at and `restore_cat` can tell which ones this delete hid.
+    sqlx::query(
+        "UPDATE cat_matches SET deleted_at = CURRENT_TIMESTAMP WHERE (user_cat_id = $1 OR match_cat_id = $1) AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await
+}
+
+/// Returns the owner of a soft-deleted cat.
+pub async fn find_deleted_cat_owner(pool: &PgPool, id: i32) -> Result<i32, sqlx::Error> {
+    sqlx::query_scalar::<_, i32>(
+        "SELECT user_id FROM cats WHERE id = $1 AND deleted_at IS NOT NULL",
+    )
+    .bind(id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Clears `deleted_at` if the cat was deleted less than `grace_days` ago,
+/// otherwise returns `RowNotFound`. Match requests hidden by the delete come
+/// back while the other cat is still live and unmatched. If that brings back
+/// an approved match, the other pending requests of both cats are rejected,
+/// as [`approve_match`](crate::repositories::r#match::approve_match) does.
+pub async fn restore_cat(
+    pool: &PgPool,
+    id: i32,
+    grace_days: i32,
+) -> Result<CreateCatResponse, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let deleted_at = sqlx::query_scalar::<_, chrono::NaiveDateTime>(
+        "SELECT deleted_at FROM cats WHERE id = $1 AND deleted_at > CURRENT_TIMESTAMP - make_interval(days => $2) FOR UPDATE",
+    )
+    .bind(id)
+    .bind(grace_days)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "WITH restored AS (UPDATE cat_matches m SET deleted_at = NULL FROM cats other WHERE m.deleted_at = $2 AND (m.user_cat_id = $1 OR m.match_cat_id = $1) AND other.id = CASE WHEN m.user_cat_id = $1 THEN m.match_cat_id ELSE m.user_cat_id END AND other.deleted_at IS NULL AND NOT other.has_matched RETURNING m.status, other.id AS other_id) UPDATE cats SET has_matched = TRUE WHERE id IN (SELECT other_id FROM restored WHERE status = 'approved')",
+    )
+    .bind(id)
+    .bind(deleted_at)
+    .execute(&mut *tx)
+    .await?;
+
+    // Includes requests either cat received while this one was deleted.
+    sqlx::query(
+        "WITH matched AS (SELECT user_cat_id AS cat_id FROM cat_matches WHERE (user_cat_id = $1 OR match_cat_id = $1) AND status = 'approved' AND deleted_at IS NULL UNION SELECT match_cat_id FROM cat_matches WHERE (user_cat_id = $1 OR match_cat_id = $1) AND status = 'approved' AND deleted_at IS NULL) UPDATE cat_matches SET status = 'rejected' WHERE status = 'pending' AND deleted_at IS NULL AND (user_cat_id IN (SELECT cat_id FROM matched) OR match_cat_id IN (SELECT cat_id FROM matched))",
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
+
+    let cat = sqlx::query_as::<_, CreateCatResponse>(
+        "UPDATE cats SET deleted_at = NULL, has_matched = EXISTS (SELECT 1 FROM cat_matches WHERE (user_cat_id = $1 OR match_cat_id = $1) AND status = 'approved' AND deleted_at IS NULL) WHERE id = $1 RETURNING id, created_at, updated_at, version",
+    )
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(cat)
+}
+
+/// Hard-deletes cats whose restore window has passed. Their match requests
+/// survive with the cat reference set to `NULL`.
+pub async fn purge_deleted_cats(pool: &PgPool, grace_days: i32) -> Result<u64, sqlx::Error> {
+    sqlx::query(
+        "DELETE FROM cats WHERE deleted_at <= CURRENT_TIMESTAMP - make_interval(days => $1)",
+    )
+    .bind(grace_days)
+    .execute(pool)
+    .await
+    .map(|result| result.rows_affected())
+}
+
+/// Escapes the `LIKE` wildcards in user input so it is matched literally.
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
 }
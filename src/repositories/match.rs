@@ -0,0 +1,155 @@
+use crate::entities::{
+    cat::CatResponse,
+    r#match::{CatMatch, CreateMatchRequest, CreateMatchResponse, MatchIssuer, MatchResponse},
+};
+use sqlx::{postgres::PgRow, PgPool, Row};
+
+pub async fn insert_match(
+    pool: &PgPool,
+    issuer_id: i32,
+    payload: CreateMatchRequest,
+) -> Result<CreateMatchResponse, sqlx::Error> {
+    sqlx::query_as::<_, CreateMatchResponse>(
+        "INSERT INTO cat_matches (issuer_id, user_cat_id, match_cat_id, message) VALUES ($1, $2, $3, $4) RETURNING id, created_at",
+    )
+    .bind(issuer_id)
+    .bind(payload.user_cat_id)
+    .bind(payload.match_cat_id)
+    .bind(payload.message)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn find_one_match(pool: &PgPool, id: i32) -> Result<CatMatch, sqlx::Error> {
+    sqlx::query_as::<_, CatMatch>(
+        "SELECT id, user_cat_id, match_cat_id, status, message, created_at, deleted_at FROM cat_matches WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Approves a pending match request, marks both cats as matched and rejects
+/// every other pending request involving either cat, all in one transaction.
+/// Returns `None`, changing nothing, when either cat is already matched.
+pub async fn approve_match(pool: &PgPool, id: i32) -> Result<Option<CatMatch>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let cat_match = sqlx::query_as::<_, CatMatch>(
+        "UPDATE cat_matches SET status = 'approved' WHERE id = $1 AND status = 'pending' AND deleted_at IS NULL RETURNING id, user_cat_id, match_cat_id, status, message, created_at, deleted_at",
+    )
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    // Locks both cats, so a concurrent approval for either one waits here and
+    // then finds it matched.
+    let matched =
+        sqlx::query("UPDATE cats SET has_matched = TRUE WHERE id IN ($1, $2) AND NOT has_matched")
+            .bind(cat_match.user_cat_id)
+            .bind(cat_match.match_cat_id)
+            .execute(&mut *tx)
+            .await?;
+
+    if matched.rows_affected() != 2 {
+        tx.rollback().await?;
+        return Ok(None);
+    }
+
+    sqlx::query(
+        "UPDATE cat_matches SET status = 'rejected' WHERE id <> $1 AND status = 'pending' AND deleted_at IS NULL AND (user_cat_id IN ($2, $3) OR match_cat_id IN ($2, $3))",
+    )
+    .bind(cat_match.id)
+    .bind(cat_match.user_cat_id)
+    .bind(cat_match.match_cat_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Some(cat_match))
+}
+
+pub async fn reject_match(pool: &PgPool, id: i32) -> Result<CatMatch, sqlx::Error> {
+    sqlx::query_as::<_, CatMatch>(
+        "UPDATE cat_matches SET status = 'rejected' WHERE id = $1 AND status = 'pending' AND deleted_at IS NULL RETURNING id, user_cat_id, match_cat_id, status, message, created_at, deleted_at",
+    )
+    .bind(id)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn has_active_matches(pool: &PgPool, cat_id: i32) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS (SELECT 1 FROM cat_matches WHERE status IN ('pending', 'approved') AND deleted_at IS NULL AND (user_cat_id = $1 OR match_cat_id = $1))",
+    )
+    .bind(cat_id)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn find_many_matches(
+    pool: &PgPool,
+    user_id: i32,
+) -> Result<Vec<MatchResponse>, sqlx::Error> {
+    sqlx::query(
+        "SELECT m.id, m.message, m.status, m.created_at, m.deleted_at, \
+            u.name AS issuer_name, u.email AS issuer_email, \
+            uc.id AS uc_id, uc.name AS uc_name, uc.race AS uc_race, uc.sex AS uc_sex, \
+            uc.age_in_month AS uc_age_in_month, uc.description AS uc_description, \
+            uc.img_urls AS uc_img_urls, uc.created_at AS uc_created_at, \
+            uc.updated_at AS uc_updated_at, \
+            uc.has_matched AS uc_has_matched, \
+            mc.id AS mc_id, mc.name AS mc_name, mc.race AS mc_race, mc.sex AS mc_sex, \
+            mc.age_in_month AS mc_age_in_month, mc.description AS mc_description, \
+            mc.img_urls AS mc_img_urls, mc.created_at AS mc_created_at, \
+            mc.updated_at AS mc_updated_at, \
+            mc.has_matched AS mc_has_matched \
+        FROM cat_matches m \
+        LEFT JOIN cats uc ON uc.id = m.user_cat_id AND uc.deleted_at IS NULL \
+        LEFT JOIN cats mc ON mc.id = m.match_cat_id AND mc.deleted_at IS NULL \
+        JOIN users u ON u.id = m.issuer_id \
+        WHERE uc.user_id = $1 OR mc.user_id = $1 \
+        ORDER BY m.created_at DESC, m.id DESC",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .map(|rows| {
+        rows.iter()
+            .map(|row| MatchResponse {
+                id: row.get("id"),
+                issued_by: MatchIssuer {
+                    name: row.get("issuer_name"),
+                    email: row.get("issuer_email"),
+                },
+                match_cat_detail: cat_from_row(row, "mc_"),
+                user_cat_detail: cat_from_row(row, "uc_"),
+                message: row.get("message"),
+                status: row.get("status"),
+                created_at: row.get("created_at"),
+                deleted_at: row.get("deleted_at"),
+            })
+            .collect()
+    })
+}
+
+/// Reads a joined cat out of `row`, or `None` when the cat has been deleted.
+fn cat_from_row(row: &PgRow, prefix: &str) -> Option<CatResponse> {
+    let column = |name: &str| format!("{}{}", prefix, name);
+
+    let id: Option<i32> = row.get(column("id").as_str());
+
+    id.map(|id| CatResponse {
+        id,
+        name: row.get(column("name").as_str()),
+        race: row.get(column("race").as_str()),
+        sex: row.get(column("sex").as_str()),
+        age_in_month: row.get(column("age_in_month").as_str()),
+        description: row.get(column("description").as_str()),
+        img_urls: row.get(column("img_urls").as_str()),
+        created_at: row.get(column("created_at").as_str()),
+        updated_at: row.get(column("updated_at").as_str()),
+        has_matched: row.get(column("has_matched").as_str()),
+    })
+}
@@ -1,2 +1,6 @@
+pub mod breed;
 pub mod cat;
+pub mod r#match;
+pub mod refresh_token;
+pub mod revoked_token;
 pub mod user;
@@ -0,0 +1,132 @@
+use crate::entities::refresh_token::RefreshRotation;
+use chrono::NaiveDateTime;
+use sqlx::{PgExecutor, PgPool, Row};
+
+pub async fn insert_refresh_token<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i32,
+    family_id: &str,
+    token_hash: &str,
+    ttl_days: i32,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO refresh_tokens (user_id, family_id, token_hash, expires_at) VALUES ($1, $2, $3, CURRENT_TIMESTAMP + make_interval(days => $4))",
+    )
+    .bind(user_id)
+    .bind(family_id)
+    .bind(token_hash)
+    .bind(ttl_days)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Exchanges the token hashed as `token_hash` for `new_token_hash` in the same
+/// family. Presenting a token that was already rotated revokes the family.
+pub async fn rotate_refresh_token(
+    pool: &PgPool,
+    token_hash: &str,
+    new_token_hash: &str,
+    ttl_days: i32,
+) -> Result<RefreshRotation, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let row = sqlx::query(
+        "SELECT rt.id, rt.user_id, rt.family_id, rt.rotated_at, rt.revoked_at, \
+            rt.expires_at > CURRENT_TIMESTAMP AS live, u.email \
+        FROM refresh_tokens rt \
+        JOIN users u ON u.id = rt.user_id \
+        WHERE rt.token_hash = $1 \
+        FOR UPDATE OF rt",
+    )
+    .bind(token_hash)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(row) = row else {
+        return Ok(RefreshRotation::Invalid);
+    };
+
+    let family_id: String = row.get("family_id");
+    let rotated_at: Option<NaiveDateTime> = row.get("rotated_at");
+    let revoked_at: Option<NaiveDateTime> = row.get("revoked_at");
+    let live: bool = row.get("live");
+
+    if revoked_at.is_some() {
+        return Ok(RefreshRotation::Invalid);
+    }
+
+    if rotated_at.is_some() {
+        revoke_family(&mut *tx, &family_id).await?;
+        tx.commit().await?;
+        return Ok(RefreshRotation::Reused);
+    }
+
+    if !live {
+        return Ok(RefreshRotation::Invalid);
+    }
+
+    let user_id: i32 = row.get("user_id");
+
+    sqlx::query("UPDATE refresh_tokens SET rotated_at = CURRENT_TIMESTAMP WHERE id = $1")
+        .bind(row.get::<i32, _>("id"))
+        .execute(&mut *tx)
+        .await?;
+
+    insert_refresh_token(&mut *tx, user_id, &family_id, new_token_hash, ttl_days).await?;
+
+    tx.commit().await?;
+
+    Ok(RefreshRotation::Rotated {
+        user_id,
+        email: row.get("email"),
+    })
+}
+
+async fn revoke_family<'e, E: PgExecutor<'e>>(
+    executor: E,
+    family_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE refresh_tokens SET revoked_at = CURRENT_TIMESTAMP WHERE family_id = $1 AND revoked_at IS NULL",
+    )
+    .bind(family_id)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Revokes the family of the token hashed as `token_hash`, if it belongs to
+/// `user_id`.
+pub async fn revoke_refresh_token_family(
+    pool: &PgPool,
+    user_id: i32,
+    token_hash: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE refresh_tokens SET revoked_at = CURRENT_TIMESTAMP WHERE revoked_at IS NULL AND family_id = (SELECT family_id FROM refresh_tokens WHERE token_hash = $1 AND user_id = $2)",
+    )
+    .bind(token_hash)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Revokes every live refresh token of `user_id`, ending all their sessions.
+pub async fn revoke_user_refresh_tokens<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i32,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE refresh_tokens SET revoked_at = CURRENT_TIMESTAMP WHERE user_id = $1 AND revoked_at IS NULL",
+    )
+    .bind(user_id)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
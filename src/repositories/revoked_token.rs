@@ -0,0 +1,33 @@
+use sqlx::{PgExecutor, PgPool};
+
+/// Revokes the token `jti` until `exp`, its own expiry as a Unix timestamp.
+pub async fn insert_revoked_token<'e, E: PgExecutor<'e>>(
+    executor: E,
+    jti: &str,
+    exp: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO revoked_tokens (jti, expires_at) VALUES ($1, to_timestamp($2)) ON CONFLICT (jti) DO NOTHING",
+    )
+    .bind(jti)
+    .bind(exp as f64)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn is_token_revoked(pool: &PgPool, jti: &str) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM revoked_tokens WHERE jti = $1)")
+        .bind(jti)
+        .fetch_one(pool)
+        .await
+}
+
+/// Drops revocations for tokens that have expired on their own.
+pub async fn purge_revoked_tokens(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    sqlx::query("DELETE FROM revoked_tokens WHERE expires_at <= CURRENT_TIMESTAMP")
+        .execute(pool)
+        .await
+        .map(|result| result.rows_affected())
+}
@@ -1,9 +1,9 @@
-use crate::entities::user::{CreateUser, FilterUser, User};
-use sqlx::{PgPool, QueryBuilder, Row};
+use crate::entities::user::{CreateUser, FilterUser, UpdateUserRequest, User};
+use sqlx::{PgExecutor, PgPool, QueryBuilder, Row};
 
 pub async fn insert_user(pool: &PgPool, user: CreateUser) -> Result<User, sqlx::Error> {
     sqlx::query_as::<_, User>(
-        "INSERT INTO users (name, email, password) VALUES ($1, $2, $3) RETURNING id, name, email, password",
+        "INSERT INTO users (name, email, password) VALUES ($1, $2, $3) RETURNING id, name, email, password, updated_at",
     )
     .bind(user.name.to_string())
     .bind(user.email.to_string())
@@ -13,8 +13,9 @@ pub async fn insert_user(pool: &PgPool, user: CreateUser) -> Result<User, sqlx::
 }
 
 pub async fn find_one_user(pool: &PgPool, filter: FilterUser) -> Result<User, sqlx::Error> {
-    let mut query =
-        QueryBuilder::<sqlx::Postgres>::new("SELECT id, name, email, password FROM users WHERE ");
+    let mut query = QueryBuilder::<sqlx::Postgres>::new(
+        "SELECT id, name, email, password, updated_at FROM users WHERE ",
+    );
     let mut has_condition = false;
 
     if let Some(id) = filter.id {
@@ -32,8 +33,9 @@ pub async fn find_one_user(pool: &PgPool, filter: FilterUser) -> Result<User, sq
         if has_condition {
             query.push(" AND ");
         }
-        query.push("email = ");
+        query.push("LOWER(email) = LOWER(");
         query.push_bind(email);
+        query.push(")");
         has_condition = true;
     }
 
@@ -54,5 +56,41 @@ pub async fn find_one_user(pool: &PgPool, filter: FilterUser) -> Result<User, sq
         name: row.get("name"),
         email: row.get("email"),
         password: row.get("password"),
+        updated_at: row.get("updated_at"),
     })
 }
+
+/// Updates only the fields present in `user`, which must not be empty. A
+/// taken email fails with a unique violation, whatever its casing.
+pub async fn update_user<'e, E: PgExecutor<'e>>(
+    executor: E,
+    id: i32,
+    user: UpdateUserRequest,
+) -> Result<User, sqlx::Error> {
+    let mut query = QueryBuilder::<sqlx::Postgres>::new("UPDATE users SET ");
+    let mut fields = query.separated(", ");
+
+    if let Some(name) = user.name {
+        fields.push("name = ");
+        fields.push_bind_unseparated(name);
+    }
+
+    if let Some(email) = user.email {
+        fields.push("email = ");
+        fields.push_bind_unseparated(email);
+    }
+
+    query.push(" WHERE id = ");
+    query.push_bind(id);
+    query.push(" RETURNING id, name, email, password, updated_at");
+
+    query.build_query_as::<User>().fetch_one(executor).await
+}
+
+pub async fn is_admin(pool: &PgPool, id: i32) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>("SELECT is_admin FROM users WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map(|is_admin| is_admin.unwrap_or(false))
+}